@@ -0,0 +1,37 @@
+// Timing for the benchmarks, which only use std rather than a benchmark harness. Run them with
+// `cargo bench`, which builds them optimized.
+
+use std::{
+    hint,
+    time::{Duration, Instant},
+};
+
+// How long each benchmark is run for after warming up
+const MEASURE: Duration = Duration::from_secs(2);
+
+// Runs `f` over and over, and prints and returns the average time it took
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    // Warm up, and find out roughly how long a run takes
+    let started = Instant::now();
+    hint::black_box(f());
+    let first = started.elapsed().max(Duration::from_nanos(1));
+
+    let runs = (MEASURE.as_nanos() / first.as_nanos()).clamp(1, 1_000_000) as u32;
+    let started = Instant::now();
+    for _ in 0..runs {
+        hint::black_box(f());
+    }
+    let average = started.elapsed() / runs;
+
+    println!("{name:<40} {average:>12.2?} per run ({runs} runs)");
+    average
+}
+
+// Prints how much faster `new` is than `old`
+pub fn speedup(old: Duration, new: Duration) {
+    println!(
+        "{:<40} {:>11.2}x\n",
+        "speedup",
+        old.as_secs_f64() / new.as_secs_f64()
+    );
+}
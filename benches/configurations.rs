@@ -0,0 +1,66 @@
+// Compares generating every placement of the classic tiles with and without checking each offset
+// against the board's bounds first, which skips the offsets that can't fit before encoding them
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use date_puzzle::{variant, EncodingBoard, Puzzle, Tile};
+
+// Tries every cell of the board as an offset for each orientation, leaving it to `encode` to turn
+// down the ones past the edges
+fn every_offset(board: &EncodingBoard, tile: &Tile, puzzle: &Puzzle) -> BTreeSet<u64> {
+    let mut configs = BTreeSet::new();
+    for tile in tile.orientations(puzzle.transforms()) {
+        for offset in board.points() {
+            if let Some(enc) = board.encode(tile.offset_points(offset)) {
+                configs.insert(enc);
+            }
+        }
+    }
+    configs
+}
+
+// Skips the offsets which take the tile's bounding box off the board, as the puzzle does
+fn fitting_offsets(board: &EncodingBoard, tile: &Tile, puzzle: &Puzzle) -> BTreeSet<u64> {
+    let mut configs = BTreeSet::new();
+    let bounds = board.bounds();
+    for tile in tile.orientations(puzzle.transforms()) {
+        let tile_bounds = tile.bounding_box();
+        for offset in board.points() {
+            if !tile_bounds.fits_within(offset, &bounds) {
+                continue;
+            }
+            if let Some(enc) = board.encode(tile.offset_points(offset)) {
+                configs.insert(enc);
+            }
+        }
+    }
+    configs
+}
+
+fn main() {
+    let puzzle = Puzzle::from_variant(variant::classic()).unwrap();
+    let board = puzzle.board();
+    let generate = |configs: fn(&EncodingBoard, &Tile, &Puzzle) -> BTreeSet<u64>| {
+        puzzle
+            .tiles()
+            .iter()
+            .map(|tile| configs(board, tile, &puzzle))
+            .collect::<Vec<_>>()
+    };
+
+    // Skipping offsets mustn't change which placements there are
+    assert_eq!(generate(every_offset), puzzle.configurations());
+    assert_eq!(generate(fitting_offsets), puzzle.configurations());
+
+    let old = common::bench("configurations/every offset", || generate(every_offset));
+    let new = common::bench("configurations/fitting offsets", || {
+        generate(fitting_offsets)
+    });
+    common::speedup(old, new);
+
+    common::bench("configurations/building the puzzle", || {
+        Puzzle::from_variant(variant::classic()).unwrap()
+    });
+}
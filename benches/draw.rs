@@ -0,0 +1,51 @@
+// Compares drawing every solution for a date, as `--all` does, on a new board for each solution
+// against one board cleared and drawn over each time
+
+mod common;
+
+use date_puzzle::{
+    date::{Date, Month},
+    entity::DecodingBoard,
+    render::{Format, Style},
+    variant, Puzzle,
+};
+
+fn main() {
+    let puzzle = Puzzle::from_variant(variant::classic()).unwrap();
+    let date = Date {
+        year: None,
+        month: Month::October,
+        day: 13,
+    };
+    let goal = puzzle.board().date_points(date);
+    let solutions = puzzle.solver().solutions(&goal).collect::<Vec<_>>();
+    println!("{} solutions for {date}\n", solutions.len());
+
+    let format = Format::Ascii(Style::PLAIN);
+    let fresh = || {
+        solutions
+            .iter()
+            .map(|solution| {
+                let mut board = DecodingBoard::from(puzzle.board());
+                format.render_solution(&mut board, solution).unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut board = DecodingBoard::from(puzzle.board());
+    let mut reused = || {
+        solutions
+            .iter()
+            .map(|solution| format.render_solution(&mut board, solution).unwrap())
+            .collect::<Vec<_>>()
+    };
+    // Reusing the board mustn't change what gets drawn
+    assert_eq!(fresh(), reused());
+
+    let old = common::bench("draw/new board per solution", fresh);
+    let new = common::bench("draw/one board", reused);
+    common::speedup(old, new);
+
+    common::bench("draw/finding the solutions", || {
+        puzzle.solver().solutions(&goal).count()
+    });
+}
@@ -0,0 +1,62 @@
+// Compares generating every placement of the classic tiles when each cell is looked up in a hash
+// map from points to bits, as the board used to, against the board's own dense lookup
+
+mod common;
+
+use std::collections::{BTreeSet, HashMap};
+
+use date_puzzle::{variant, EncodingBoard, Point, Puzzle};
+
+// Something which turns the cells of a placement into their bits
+trait Lookup {
+    fn encode(&self, points: impl Iterator<Item = Point>) -> Option<u64>;
+}
+
+impl Lookup for HashMap<Point, u64> {
+    fn encode(&self, mut points: impl Iterator<Item = Point>) -> Option<u64> {
+        points.try_fold(0, |enc, point| Some(enc | self.get(&point)?))
+    }
+}
+
+impl Lookup for EncodingBoard {
+    fn encode(&self, points: impl Iterator<Item = Point>) -> Option<u64> {
+        EncodingBoard::encode(self, points)
+    }
+}
+
+// Every placement of every tile, with each one encoded by `lookup`
+fn configurations(puzzle: &Puzzle, lookup: &impl Lookup) -> Vec<BTreeSet<u64>> {
+    let board = puzzle.board();
+    puzzle
+        .tiles()
+        .iter()
+        .map(|tile| {
+            let mut configs = BTreeSet::new();
+            for tile in tile.orientations(puzzle.transforms()) {
+                for offset in board.points() {
+                    if let Some(enc) = lookup.encode(tile.offset_points(offset)) {
+                        configs.insert(enc);
+                    }
+                }
+            }
+            configs
+        })
+        .collect()
+}
+
+fn main() {
+    let puzzle = Puzzle::from_variant(variant::classic()).unwrap();
+    let board = puzzle.board();
+    let map = board
+        .cells()
+        .map(|(bit, point)| (point, 1 << bit))
+        .collect::<HashMap<_, _>>();
+
+    // Both lookups have to find the same placements
+    assert_eq!(configurations(&puzzle, &map), puzzle.configurations());
+    assert_eq!(configurations(&puzzle, board), puzzle.configurations());
+
+    let old = common::bench("encode/hash map", || configurations(&puzzle, &map));
+    let new = common::bench("encode/dense", || configurations(&puzzle, board));
+    common::speedup(old, new);
+}
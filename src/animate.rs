@@ -0,0 +1,190 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "png")]
+use crate::render::Recording;
+use crate::{
+    backtrack::Stats,
+    entity::{DecodingBoard, EncodingBoard, Square},
+    render::Format,
+    BitBoard,
+};
+
+// How long an image holds its last frame, so the finished board can be seen before it loops
+#[cfg(feature = "png")]
+const HOLD: Duration = Duration::from_secs(2);
+
+// The most frames an image gets unless told otherwise, which keeps a long search's image to a few
+// megabytes
+pub const DEFAULT_MAX_FRAMES: usize = 1000;
+
+// How the animation is paced, and which steps it shows
+pub struct Options {
+    // How long to pause after each frame, or how long each frame of an image is shown
+    pub delay: Duration,
+    // The most frames to draw in a second, with the steps in between not drawn
+    pub fps: Option<u32>,
+    // Leaves out the steps which take pieces back off the board
+    pub forward_only: bool,
+    // How many frames an image can have, not counting the last one showing how the search ended
+    #[cfg_attr(not(feature = "png"), allow(dead_code))]
+    pub max_frames: usize,
+}
+
+// Where the frames go
+enum Target {
+    // Drawn over the previous frame on stdout, remembering how many lines that took up
+    Terminal {
+        frame_lines: usize,
+    },
+    // Kept as the frames of an animated image
+    #[cfg(feature = "png")]
+    Image(Recording),
+}
+
+// Redraws the board in place on stdout as the search places and removes pieces, or records each
+// step as a frame of an image when the format is `png`
+pub struct Animation<'a, B = u64> {
+    encoding_board: &'a EncodingBoard<B>,
+    placements: &'a [B],
+    initial_board: B,
+    // Drawn over for each frame rather than made again
+    board: DecodingBoard,
+    format: Format,
+    options: Options,
+    // The shortest time between frames, with events in between not drawn
+    min_interval: Duration,
+    // The placement currently on the board for each piece
+    placed: Vec<Option<B>>,
+    last_frame: Option<Instant>,
+    target: Target,
+    pub steps: u64,
+}
+
+impl<'a, B: BitBoard> Animation<'a, B> {
+    pub fn new(
+        encoding_board: &'a EncodingBoard<B>,
+        placements: &'a [B],
+        pieces: usize,
+        initial_board: B,
+        format: Format,
+        options: Options,
+    ) -> Self {
+        let board = DecodingBoard::from(encoding_board);
+        let target = match format {
+            #[cfg(feature = "png")]
+            Format::Png(scale) => Target::Image(Recording::new(&board, scale)),
+            _ => Target::Terminal { frame_lines: 0 },
+        };
+
+        Self {
+            encoding_board,
+            placements,
+            initial_board,
+            board,
+            format,
+            min_interval: options
+                .fps
+                .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps),
+            options,
+            placed: vec![None; pieces],
+            last_frame: None,
+            target,
+            steps: 0,
+        }
+    }
+
+    fn step(&mut self, shown: bool) {
+        self.steps += 1;
+        if !shown {
+            return;
+        }
+
+        let now = Instant::now();
+        if self
+            .last_frame
+            .is_some_and(|last| now - last < self.min_interval)
+        {
+            return;
+        }
+        self.last_frame = Some(now);
+        self.draw();
+    }
+
+    // Draws the current state of the search, with unplaced regions left empty
+    pub fn draw(&mut self) {
+        self.frame(false);
+    }
+
+    // Draws how the search ended, which an image gets even once it has all the frames it can
+    // have, and returns the image if there is one
+    pub fn finish(mut self) -> Option<Vec<u8>> {
+        self.frame(true);
+        match self.target {
+            Target::Terminal { .. } => None,
+            #[cfg(feature = "png")]
+            Target::Image(recording) => Some(recording.finish(self.options.delay, HOLD)),
+        }
+    }
+
+    #[cfg_attr(not(feature = "png"), allow(unused_variables))]
+    fn frame(&mut self, last: bool) {
+        // A full image skips the rest of the search, rather than drawing frames it won't keep
+        #[cfg(feature = "png")]
+        if let Target::Image(recording) = &self.target {
+            if !last && recording.frames() >= self.options.max_frames {
+                return;
+            }
+        }
+
+        let board = &mut self.board;
+        let all = self
+            .encoding_board
+            .encode(self.encoding_board.points())
+            .unwrap_or(B::EMPTY);
+        let fits = "placements only cover cells of the board";
+        board.fill(all, Square::Empty).expect(fits);
+        for (id, placement) in self.placed.iter().enumerate() {
+            if let Some(enc) = placement {
+                board.decode(*enc, Some(id as u8), false).expect(fits);
+            }
+        }
+        board.decode(self.initial_board, None, false).expect(fits);
+
+        match &mut self.target {
+            Target::Terminal { frame_lines } => {
+                let frame = self
+                    .format
+                    .render(board)
+                    .expect("only text formats are animated in the terminal");
+                let mut stdout = io::stdout().lock();
+                // Move back up to the top of the previous frame and draw over it
+                if *frame_lines > 0 {
+                    write!(stdout, "\x1b[{frame_lines}A").ok();
+                }
+                write!(stdout, "{frame}").ok();
+                stdout.flush().ok();
+                *frame_lines = frame.lines().count();
+
+                thread::sleep(self.options.delay);
+            }
+            #[cfg(feature = "png")]
+            Target::Image(recording) => recording.push(board),
+        }
+    }
+}
+
+impl<B: BitBoard> Stats for Animation<'_, B> {
+    fn place(&mut self, piece: usize, placement: usize) {
+        self.placed[piece] = Some(self.placements[placement]);
+        self.step(true);
+    }
+
+    fn backtrack(&mut self, piece: usize) {
+        self.placed[piece] = None;
+        self.step(!self.options.forward_only);
+    }
+}
@@ -1,88 +1,689 @@
-pub fn backtrack(
-    initial_board: u64,
-    placements: &[u64],
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use crate::{bitboard::BitBoard, cell::CellOrder, mrv::Mrv};
+
+#[cfg(feature = "dlx")]
+use crate::dlx::Dlx;
+
+// How many iterations of the search loop run between checks of the deadline, budget and
+// cancellation. Must be a power of two.
+const DEADLINE_CHECK_INTERVAL: u32 = 4096;
+
+// Why a search stopped before it could finish, in which case it's unknown whether there are more
+// solutions
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interrupted {
+    // It ran past its deadline, or used up its budget
+    TimedOut,
+    // Its `CancelToken` was cancelled
+    Cancelled,
+}
+
+// Lets another thread stop a search. Clones share the same flag, so any of them can cancel every
+// search given one.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// When a search should give up, if ever
+#[derive(Clone, Default, Debug)]
+pub struct Limits {
+    pub deadline: Option<Instant>,
+    pub cancel: Option<CancelToken>,
+}
+
+impl Limits {
+    pub fn deadline(deadline: Option<Instant>) -> Self {
+        Self {
+            deadline,
+            cancel: None,
+        }
+    }
+}
+
+// Hooks for instrumenting the search. The unit type implements these as no-ops so that searches
+// which don't care about statistics pay nothing for them.
+pub trait Stats {
+    // A placement of piece `piece` was checked against the board
+    fn attempt(&mut self, _piece: usize) {}
+
+    // Piece `piece` was put on the board using the placement at index `placement`
+    fn place(&mut self, _piece: usize, _placement: usize) {}
+
+    // Piece `piece` was taken back off the board
+    fn backtrack(&mut self, _piece: usize) {}
+
+    // `depth` pieces are now on the board
+    fn depth(&mut self, _depth: usize) {}
+
+    // Every piece is on the board, just before the solution is reported
+    fn solution(&mut self) {}
+
+    // Whether the search should give up, checked as often as the deadline
+    fn exhausted(&self) -> bool {
+        false
+    }
+
+    // Whether the search should stop as if it had been cancelled, checked as often as the
+    // deadline
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl Stats for () {}
+
+// Which strategy walks the solutions. They all find the same solutions, but visit them in
+// different orders and at very different speeds depending on the date.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Order {
+    // Place the pieces in a fixed order, trying each of a piece's placements in turn
+    Static,
+    // Place whichever piece has the fewest placements left next
+    Mrv,
+    // Cover the first empty cell next, with every piece that fits there
+    Cell,
+    // Knuth's Algorithm X with dancing links, choosing the piece or cell with fewest options
+    #[cfg(feature = "dlx")]
+    Dlx,
+}
+
+impl Order {
+    const NAMES: [&'static str; 4] = ["static", "mrv", "cell", "dlx"];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "static" => Ok(Order::Static),
+            "mrv" => Ok(Order::Mrv),
+            "cell" => Ok(Order::Cell),
+            #[cfg(feature = "dlx")]
+            "dlx" => Ok(Order::Dlx),
+            #[cfg(not(feature = "dlx"))]
+            "dlx" => Err(
+                "the `dlx` order isn't compiled in, rebuild with `--features dlx` to use it"
+                    .to_owned(),
+            ),
+            _ => Err(format!(
+                "unknown order `{s}`, expected one of: {}",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Order::Static => "static",
+            Order::Mrv => "mrv",
+            Order::Cell => "cell",
+            #[cfg(feature = "dlx")]
+            Order::Dlx => "dlx",
+        }
+    }
+}
+
+// A way of walking every solution, which each `Order` is implemented by. Solutions are reported
+// as one placement index per piece, in piece order, no matter which order the pieces were placed
+// in.
+pub trait Strategy {
+    fn search<B: BitBoard>(
+        &self,
+        initial_board: B,
+        placements: &[B],
+        placement_indices: &[usize],
+        watchdog: &mut Watchdog<'_>,
+        stats: &mut impl Stats,
+        on_solution: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<(), Interrupted>;
+}
+
+// Decides when a search has run for too long or been cancelled. Strategies call `check` once
+// per step.
+pub struct Watchdog<'a> {
+    limits: &'a Limits,
+    iterations: u32,
+}
+
+impl<'a> Watchdog<'a> {
+    pub fn new(limits: &'a Limits) -> Self {
+        Self {
+            limits,
+            iterations: 0,
+        }
+    }
+
+    // Reading the clock and the cancellation flag is comparatively slow, so only do it every so
+    // often
+    #[inline]
+    pub fn check(&mut self, stats: &impl Stats) -> Result<(), Interrupted> {
+        self.iterations = self.iterations.wrapping_add(1);
+        if self.iterations & (DEADLINE_CHECK_INTERVAL - 1) != 0 {
+            return Ok(());
+        }
+
+        if stats.cancelled()
+            || self
+                .limits
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+        {
+            return Err(Interrupted::Cancelled);
+        }
+        if stats.exhausted()
+            || self
+                .limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Interrupted::TimedOut);
+        }
+        Ok(())
+    }
+}
+
+// Counters describing how much work a search did
+#[derive(Clone, Default, Debug)]
+pub struct SearchStats {
+    // Placements checked against the board
+    pub attempts: u64,
+    pub backtracks: u64,
+    pub max_depth: usize,
+    // The attempts made for each piece, indexed by piece
+    pub piece_attempts: Vec<u64>,
+}
+
+// A snapshot of how far a search has got, handed to a `ProgressHook`'s callback
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SearchProgress {
+    // How many pieces are on the board
+    pub depth: usize,
+    // How many times a piece has been put on the board
+    pub nodes: u64,
+    pub solutions: u64,
+    // How much of the first piece's placements have been tried, from 0 to 1. The search doesn't
+    // take the same time under each of them, so this is only a rough estimate of how much is
+    // done.
+    pub fraction: f64,
+}
+
+// Calls `callback` every `interval` nodes with the search's progress. Returning
+// `ControlFlow::Break` from it stops the search with `Interrupted::Cancelled`, though not
+// necessarily straight away.
+pub struct ProgressHook<F> {
+    placement_indices: Vec<usize>,
+    interval: u64,
+    callback: F,
+    progress: SearchProgress,
+    cancelled: bool,
+}
+
+impl<F: FnMut(&SearchProgress) -> ControlFlow<()>> ProgressHook<F> {
+    pub fn new(placement_indices: Vec<usize>, interval: u64, callback: F) -> Self {
+        Self {
+            placement_indices,
+            interval: interval.max(1),
+            callback,
+            progress: SearchProgress::default(),
+            cancelled: false,
+        }
+    }
+
+    pub fn progress(&self) -> &SearchProgress {
+        &self.progress
+    }
+}
+
+impl<F: FnMut(&SearchProgress) -> ControlFlow<()>> Stats for ProgressHook<F> {
+    fn place(&mut self, piece: usize, placement: usize) {
+        self.progress.depth += 1;
+        self.progress.nodes += 1;
+
+        // Whichever piece went down first, since not every order starts with the same one
+        if self.progress.depth == 1 {
+            let start = self.placement_indices[piece];
+            let end = self.placement_indices[piece + 1];
+            self.progress.fraction = (placement - start) as f64 / (end - start) as f64;
+        }
+
+        if self.progress.nodes.is_multiple_of(self.interval) && !self.cancelled {
+            self.cancelled = (self.callback)(&self.progress).is_break();
+        }
+    }
+
+    fn backtrack(&mut self, _piece: usize) {
+        self.progress.depth -= 1;
+    }
+
+    fn solution(&mut self) {
+        self.progress.solutions += 1;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+// Limits a search to a number of placement attempts, so that hard cases can be abandoned
+pub struct NodeBudget {
+    pub remaining: u64,
+}
+
+impl Stats for NodeBudget {
+    #[inline]
+    fn attempt(&mut self, _piece: usize) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    fn exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl Stats for SearchStats {
+    #[inline]
+    fn attempt(&mut self, piece: usize) {
+        self.attempts += 1;
+        if piece >= self.piece_attempts.len() {
+            self.piece_attempts.resize(piece + 1, 0);
+        }
+        self.piece_attempts[piece] += 1;
+    }
+
+    #[inline]
+    fn backtrack(&mut self, _piece: usize) {
+        self.backtracks += 1;
+    }
+
+    #[inline]
+    fn depth(&mut self, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
+
+pub fn backtrack<B: BitBoard>(
+    initial_board: B,
+    placements: &[B],
     placement_indices: &[usize],
-) -> Option<Vec<usize>> {
-    let mut selected_placements = Box::<[usize]>::from(placement_indices);
-    let mut board_states = vec![initial_board; placement_indices.len() - 1];
-    let mut i = 0;
-    let mut board = initial_board;
+    order: Order,
+    limits: &Limits,
+    stats: &mut impl Stats,
+) -> Result<Option<Vec<usize>>, Interrupted> {
+    let mut solution = None;
+
+    search(
+        initial_board,
+        placements,
+        placement_indices,
+        order,
+        limits,
+        stats,
+        |indices| {
+            solution = Some(indices.to_vec());
+            ControlFlow::Break(())
+        },
+    )?;
+
+    if let Some(solution) = &solution {
+        debug_assert_eq!(
+            verify_solution(initial_board, placements, placement_indices, solution),
+            Ok(())
+        );
+    }
+
+    Ok(solution)
+}
 
-    loop {
-        let mut selected_placement = selected_placements[i];
-        let bound = selected_placements[i + 1];
+pub fn count<B: BitBoard>(
+    initial_board: B,
+    placements: &[B],
+    placement_indices: &[usize],
+    order: Order,
+    limits: &Limits,
+    stats: &mut impl Stats,
+) -> Result<u64, Interrupted> {
+    let mut count = 0;
+
+    search(
+        initial_board,
+        placements,
+        placement_indices,
+        order,
+        limits,
+        stats,
+        |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        },
+    )?;
+
+    Ok(count)
+}
+
+// Counts solutions, but stops as soon as `limit` of them have been found. Telling whether a
+// solution is unique only takes a limit of two, which is far faster than counting them all.
+pub fn count_up_to<B: BitBoard>(
+    initial_board: B,
+    placements: &[B],
+    placement_indices: &[usize],
+    limit: u64,
+    order: Order,
+    limits: &Limits,
+    stats: &mut impl Stats,
+) -> Result<u64, Interrupted> {
+    if limit == 0 {
+        return Ok(0);
+    }
+
+    let mut count = 0;
 
-        // Find the next valid move
-        let placement = loop {
-            let placement = placements[selected_placement];
-            if placement & board == 0 {
-                break placement;
+    search(
+        initial_board,
+        placements,
+        placement_indices,
+        order,
+        limits,
+        stats,
+        |_| {
+            count += 1;
+            if count >= limit {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             }
+        },
+    )?;
+
+    Ok(count)
+}
 
-            selected_placement += 1;
-            if selected_placement == bound {
-                break 0;
+// Why a set of placement indices isn't a solution
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum VerifyError {
+    // There has to be exactly one placement per piece
+    WrongPieceCount { expected: usize, found: usize },
+    // The index isn't one of the piece's own placements
+    OutOfRange { piece: usize, index: usize },
+    // The two pieces cover some of the same cells
+    Overlap { first: usize, second: usize },
+    // The piece covers a cell which was already filled to begin with
+    CoversInitial { piece: usize },
+    // These cells have to be covered, since the pieces are just big enough for the free cells
+    Uncovered { cells: u128 },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::WrongPieceCount { expected, found } => {
+                write!(f, "expected {expected} placements, but found {found}")
+            }
+            VerifyError::OutOfRange { piece, index } => {
+                write!(f, "placement {index} doesn't belong to piece {piece}")
+            }
+            VerifyError::Overlap { first, second } => {
+                write!(f, "pieces {first} and {second} overlap")
+            }
+            VerifyError::CoversInitial { piece } => {
+                write!(f, "piece {piece} covers a cell which was already filled")
             }
-        };
+            VerifyError::Uncovered { cells } => {
+                write!(f, "the cells {cells:#x} were left uncovered")
+            }
+        }
+    }
+}
+
+impl Error for VerifyError {}
 
-        // We ran out of possible moves, so backtrack
-        if placement == 0 {
-            // The idea here is to walk back our selected placements until we find a piece with a
-            // configuration we haven't tried. If no such pieces exist, then we're done and we
-            // couldn't find a solution.
+// Checks that `solution` picks one placement from each piece's range, that none of them overlap
+// each other or the initial board, and that together with the initial board they fill every cell
+// some placement reaches. When the pieces cover fewer cells than are free, as many cells as are
+// missing may stay empty instead.
+pub fn verify_solution<B: BitBoard>(
+    initial_board: B,
+    placements: &[B],
+    placement_indices: &[usize],
+    solution: &[usize],
+) -> Result<(), VerifyError> {
+    let pieces = placement_indices.len() - 1;
+    if solution.len() != pieces {
+        return Err(VerifyError::WrongPieceCount {
+            expected: pieces,
+            found: solution.len(),
+        });
+    }
 
-            selected_placements[i] = placement_indices[i];
+    let mut board = initial_board;
+    for (piece, &index) in solution.iter().enumerate() {
+        if !(placement_indices[piece]..placement_indices[piece + 1]).contains(&index) {
+            return Err(VerifyError::OutOfRange { piece, index });
+        }
 
-            loop {
-                if i == 0 {
-                    // We've tried everything
-                    return None;
-                }
+        let placement = placements[index];
+        if !placement.is_disjoint(initial_board) {
+            return Err(VerifyError::CoversInitial { piece });
+        }
+        if !placement.is_disjoint(board) {
+            let first = (0..piece)
+                .find(|&other| !placements[solution[other]].is_disjoint(placement))
+                .expect("the placement overlaps an earlier piece");
+            return Err(VerifyError::Overlap {
+                first,
+                second: piece,
+            });
+        }
+        board |= placement;
+    }
 
-                let bound = placement_indices[i];
-                i -= 1;
+    // Cells no placement reaches can't be covered by anything, so they don't count as part of
+    // the board
+    let full = placements
+        .iter()
+        .fold(initial_board, |cells, &placement| cells | placement);
+    // Placements built by hand don't always agree in size, so the pieces' area is taken from the
+    // first placement of each rather than the chosen ones
+    let area = placement_indices[..pieces]
+        .iter()
+        .map(|&start| placements[start].count_ones())
+        .sum::<u32>();
+    let spare = (full & !initial_board).count_ones().saturating_sub(area);
+    let uncovered = full & !board;
+    if uncovered.count_ones() > spare {
+        return Err(VerifyError::Uncovered {
+            cells: uncovered.to_u128(),
+        });
+    }
+
+    Ok(())
+}
+
+// Walks every solution in search order, handing the selected placement indices to `on_solution`.
+// The slice passed to the callback is reused between solutions, so nothing is allocated per
+// solution. The search stops early if the callback returns `ControlFlow::Break`, or fails if it's
+// still running once `deadline` passes.
+pub fn search<B: BitBoard>(
+    initial_board: B,
+    placements: &[B],
+    placement_indices: &[usize],
+    order: Order,
+    limits: &Limits,
+    stats: &mut impl Stats,
+    mut on_solution: impl FnMut(&[usize]) -> ControlFlow<()>,
+) -> Result<(), Interrupted> {
+    // With no pieces to place the initial board is trivially the only solution
+    if placement_indices.len() == 1 {
+        let _ = on_solution(&[]);
+        return Ok(());
+    }
+
+    let watchdog = &mut Watchdog::new(limits);
+    let on_solution = &mut on_solution;
+    match order {
+        Order::Static => PieceOrder.search(
+            initial_board,
+            placements,
+            placement_indices,
+            watchdog,
+            stats,
+            on_solution,
+        ),
+        Order::Mrv => Mrv.search(
+            initial_board,
+            placements,
+            placement_indices,
+            watchdog,
+            stats,
+            on_solution,
+        ),
+        Order::Cell => CellOrder.search(
+            initial_board,
+            placements,
+            placement_indices,
+            watchdog,
+            stats,
+            on_solution,
+        ),
+        #[cfg(feature = "dlx")]
+        Order::Dlx => Dlx.search(
+            initial_board,
+            placements,
+            placement_indices,
+            watchdog,
+            stats,
+            on_solution,
+        ),
+    }
+}
+
+// The original strategy, which places the pieces in order and keeps its own stack so that the
+// inner loop stays tight
+pub struct PieceOrder;
 
-                // We've tried all configurations for this piece, so check the previous one on the
-                // next iteration
-                let selected_placement = selected_placements[i];
+impl Strategy for PieceOrder {
+    fn search<B: BitBoard>(
+        &self,
+        initial_board: B,
+        placements: &[B],
+        placement_indices: &[usize],
+        watchdog: &mut Watchdog<'_>,
+        stats: &mut impl Stats,
+        on_solution: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<(), Interrupted> {
+        let pieces = placement_indices.len() - 1;
+        let mut solution = vec![0; pieces];
+        let mut selected_placements = Box::<[usize]>::from(placement_indices);
+        let mut board_states = vec![initial_board; pieces];
+        let mut i = 0;
+        let mut board = initial_board;
+
+        loop {
+            watchdog.check(stats)?;
+
+            let mut selected_placement = selected_placements[i];
+            let bound = selected_placements[i + 1];
+
+            // Find the next valid move
+            let placement = loop {
                 if selected_placement == bound {
-                    // Reset the selected placement to the first option
-                    selected_placements[i] = placement_indices[i];
+                    break B::EMPTY;
+                }
+
+                let placement = placements[selected_placement];
+                stats.attempt(i);
+                if placement.is_disjoint(board) {
+                    break placement;
                 }
-                // We haven't tried all possibilities for piece i
-                else {
-                    board = board_states[i];
-                    break;
+
+                selected_placement += 1;
+            };
+
+            // We ran out of possible moves, so backtrack
+            if placement.is_empty() {
+                // The idea here is to walk back our selected placements until we find a piece with a
+                // configuration we haven't tried. If no such pieces exist, then we're done and we
+                // couldn't find a solution.
+
+                selected_placements[i] = placement_indices[i];
+
+                loop {
+                    if i == 0 {
+                        // We've tried everything
+                        return Ok(());
+                    }
+
+                    let bound = placement_indices[i];
+                    i -= 1;
+                    stats.backtrack(i);
+
+                    // We've tried all configurations for this piece, so check the previous one on the
+                    // next iteration
+                    let selected_placement = selected_placements[i];
+                    if selected_placement == bound {
+                        // Reset the selected placement to the first option
+                        selected_placements[i] = placement_indices[i];
+                    }
+                    // We haven't tried all possibilities for piece i
+                    else {
+                        board = board_states[i];
+                        break;
+                    }
                 }
+
+                continue;
             }
 
-            continue;
-        }
+            // Store the next configuration we want to check if we backtrack
+            selected_placements[i] = selected_placement + 1;
+            stats.place(i, selected_placement);
 
-        // Store the next configuration we want to check if we backtrack
-        selected_placements[i] = selected_placement + 1;
+            // Store the current board state so we can back-track
+            board_states[i] = board;
 
-        // Store the current board state so we can back-track
-        board_states[i] = board;
+            // We'll check the next piece on the next iteration
+            i += 1;
+            stats.depth(i);
 
-        // We'll check the next piece on the next iteration
-        i += 1;
+            // We've found a solution, so report it and then resume the search from the last piece
+            if i == pieces {
+                solution
+                    .iter_mut()
+                    .zip(selected_placements.iter())
+                    .for_each(|(index, &selected)| *index = selected - 1);
+                stats.solution();
+                if on_solution(&solution).is_break() {
+                    return Ok(());
+                }
 
-        // We're done and we've found a solution
-        if i == placement_indices.len() - 1 {
-            break;
-        }
+                i -= 1;
+                stats.backtrack(i);
+                board = board_states[i];
+                continue;
+            }
 
-        // Add the placement to the board
-        board |= placement;
+            // Add the placement to the board
+            board |= placement;
+        }
     }
-
-    Some(
-        selected_placements
-            .iter()
-            .take(selected_placements.len() - 1)
-            .copied()
-            .map(|index| index - 1)
-            .collect(),
-    )
 }
@@ -0,0 +1,111 @@
+use std::{
+    fmt::{Debug, LowerHex},
+    hash::Hash,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
+};
+
+// A set of board cells with one bit per cell, which is what placements and boards are encoded
+// as. `u64` is enough for the built-in boards and is the fastest, while `u128` allows larger
+// ones.
+pub trait BitBoard:
+    Copy
+    + Eq
+    + Ord
+    + Hash
+    + Debug
+    + LowerHex
+    + Send
+    + Sync
+    + 'static
+    + BitAnd<Output = Self>
+    + BitAndAssign
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + Not<Output = Self>
+{
+    // How many cells fit
+    const BITS: u32;
+    const EMPTY: Self;
+
+    // Only the cell at `index`, which has to be less than `BITS`
+    fn bit(index: u32) -> Self;
+
+    fn count_ones(self) -> u32;
+
+    fn trailing_zeros(self) -> u32;
+
+    // Widened so that errors can show the encoding whatever its size
+    fn to_u128(self) -> u128;
+
+    #[inline]
+    fn is_empty(self) -> bool {
+        self == Self::EMPTY
+    }
+
+    #[inline]
+    fn is_disjoint(self, other: Self) -> bool {
+        (self & other).is_empty()
+    }
+
+    #[inline]
+    fn contains(self, index: u32) -> bool {
+        !self.is_disjoint(Self::bit(index))
+    }
+
+    // The index of every set bit, lowest first
+    #[inline]
+    fn ones(self) -> Ones<Self> {
+        Ones { bits: self }
+    }
+}
+
+macro_rules! impl_bit_board {
+    ($( $int:ty ),+) => {
+        $(
+            impl BitBoard for $int {
+                const BITS: u32 = <$int>::BITS;
+                const EMPTY: Self = 0;
+
+                #[inline]
+                fn bit(index: u32) -> Self {
+                    1 << index
+                }
+
+                #[inline]
+                fn count_ones(self) -> u32 {
+                    <$int>::count_ones(self)
+                }
+
+                #[inline]
+                fn trailing_zeros(self) -> u32 {
+                    <$int>::trailing_zeros(self)
+                }
+
+                fn to_u128(self) -> u128 {
+                    self as u128
+                }
+            }
+        )+
+    };
+}
+
+impl_bit_board!(u64, u128);
+
+pub struct Ones<B> {
+    bits: B,
+}
+
+impl<B: BitBoard> Iterator for Ones<B> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if self.bits.is_empty() {
+            return None;
+        }
+
+        let index = self.bits.trailing_zeros();
+        self.bits &= !B::bit(index);
+        Some(index)
+    }
+}
@@ -0,0 +1,231 @@
+use std::{fs, path::Path};
+
+use crate::{
+    date::Date,
+    entity::DecodingBoard,
+    error::Error,
+    render::{self, Format},
+    BitBoard, Context, Job,
+};
+
+// Lines longer than this many bytes are folded, as RFC 5545 requires
+const MAX_LINE: usize = 75;
+
+// Writes one all-day event per date of the year, describing one of its solutions, or a CSV of
+// every date's solution count and first solution when `csv` is set
+pub fn run<B: BitBoard>(
+    ctx: &Context<B>,
+    year: i32,
+    format: Format,
+    csv: bool,
+    output: Option<&Path>,
+) -> Result<(), Error> {
+    let jobs = Date::year_dates(year)
+        .map(|date| ctx.job(date, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let solutions = ctx.solve_all(&jobs)?;
+
+    let contents = if csv {
+        let counts = ctx.count_all(&jobs)?;
+        csv_rows(ctx, &jobs, &solutions, &counts)
+    } else {
+        ics(ctx, &jobs, &solutions, format)
+    };
+
+    let Some(path) = output else {
+        print!("{contents}");
+        return Ok(());
+    };
+
+    let failed =
+        |error| Error::InvalidInput(format!("failed to write `{}`: {error}", path.display()));
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(failed)?;
+    }
+    fs::write(path, contents).map_err(failed)?;
+    eprintln!("Wrote {} dates to {}", jobs.len(), path.display());
+
+    Ok(())
+}
+
+fn ics<B: BitBoard>(
+    ctx: &Context<B>,
+    jobs: &[Job<B>],
+    solutions: &[Option<Vec<usize>>],
+    format: Format,
+) -> String {
+    let stamp = format!("{}T000000Z", ics_date(Date::today()));
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//date-puzzle//calendar//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+    ];
+
+    let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
+    for (job, solution) in jobs.iter().zip(solutions) {
+        let description = match solution {
+            Some(solution) => rendering(ctx, &mut decoding_board, job, solution, format),
+            None => "unsolvable".to_owned(),
+        };
+
+        lines.extend([
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}@date-puzzle", job.date.numeric()),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", ics_date(job.date)),
+            format!("DTEND;VALUE=DATE:{}", ics_date(job.date.next_day())),
+            format!(
+                "SUMMARY:{}",
+                escape(&format!("Date puzzle for {}", job.date))
+            ),
+            format!("DESCRIPTION:{}", escape(&description)),
+            "TRANSP:TRANSPARENT".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+fn csv_rows<B: BitBoard>(
+    ctx: &Context<B>,
+    jobs: &[Job<B>],
+    solutions: &[Option<Vec<usize>>],
+    counts: &[u64],
+) -> String {
+    let mut out = "date,solutions,first_solution\n".to_owned();
+
+    for ((job, solution), count) in jobs.iter().zip(solutions).zip(counts) {
+        // Each tile's cells, with the lines of the coords format joined so the row stays on one
+        // line. The field always contains commas, so it's always quoted.
+        let coords = match solution {
+            Some(solution) => {
+                let coords = render::coords(&ctx.solution(job, solution));
+                format!(
+                    "\"{}\"",
+                    coords
+                        .lines()
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                        .replace('"', "\"\"")
+                )
+            }
+            None => String::new(),
+        };
+        out.push_str(&format!("{},{count},{coords}\n", job.date.numeric()));
+    }
+
+    out
+}
+
+fn rendering<B: BitBoard>(
+    ctx: &Context<B>,
+    decoding_board: &mut DecodingBoard,
+    job: &Job<B>,
+    solution: &[usize],
+    format: Format,
+) -> String {
+    let mut rendering = Vec::new();
+    crate::write_solution(
+        &mut rendering,
+        decoding_board,
+        &ctx.solution(job, solution),
+        format,
+        &ctx.tiles,
+    )
+    .expect("writing to a Vec can't fail");
+
+    String::from_utf8(rendering)
+        .expect("renderings are valid UTF-8")
+        .trim_end()
+        .to_owned()
+}
+
+// Formats the date as YYYYMMDD, which is how ICS writes dates without a time
+fn ics_date(date: Date) -> String {
+    date.numeric().replace('-', "")
+}
+
+// Escapes the characters that have a meaning in ICS text values
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => (),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Splits the line into pieces of at most `MAX_LINE` bytes, each continuation starting with a
+// space. Pieces only end on character boundaries, so multi-byte characters aren't split.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            // The leading space counts towards the continuation's length
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape("a;b,c\nd"), r"a\;b\,c\nd");
+        assert_eq!(escape("back\\slash\r\n"), r"back\\slash\n");
+        assert_eq!(escape("plain text"), "plain text");
+    }
+
+    #[test]
+    fn folds_after_75_octets() {
+        let exact = "a".repeat(MAX_LINE);
+        assert_eq!(fold(&exact), exact);
+
+        let long = "a".repeat(MAX_LINE + 1);
+        assert_eq!(fold(&long), format!("{}\r\n a", "a".repeat(MAX_LINE)));
+
+        // Continuations hold 74 octets after their leading space
+        let longer = "a".repeat(MAX_LINE + 74 + 1);
+        let lines = fold(&longer)
+            .split("\r\n")
+            .map(str::len)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [MAX_LINE, MAX_LINE, 2]);
+    }
+
+    // A character which would cross the 75th octet starts the next line instead
+    #[test]
+    fn folding_keeps_characters_whole() {
+        let line = format!("{}é", "a".repeat(MAX_LINE - 1));
+        let folded = fold(&line);
+        assert_eq!(folded, format!("{}\r\n é", "a".repeat(MAX_LINE - 1)));
+        assert!(folded.split("\r\n").all(|line| line.len() <= MAX_LINE));
+
+        let fits = format!("{}é", "a".repeat(MAX_LINE - 2));
+        assert_eq!(fold(&fits), fits);
+    }
+}
@@ -0,0 +1,144 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    backtrack::{Interrupted, Stats, Strategy, Watchdog},
+    bitboard::BitBoard,
+};
+
+// Marks a piece which isn't on the board yet
+const UNPLACED: usize = usize::MAX;
+
+// Fills the board in bit order: the first empty cell is covered by each unplaced piece that fits
+// over it in turn, so every step only considers placements that could matter there. When the
+// tiles cover fewer cells than are free, as with `--exclude-tile`, leaving the cell empty is tried
+// too.
+pub struct CellOrder;
+
+impl Strategy for CellOrder {
+    fn search<B: BitBoard>(
+        &self,
+        initial_board: B,
+        placements: &[B],
+        placement_indices: &[usize],
+        watchdog: &mut Watchdog<'_>,
+        stats: &mut impl Stats,
+        on_solution: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<(), Interrupted> {
+        let pieces = placement_indices.len() - 1;
+
+        // Cells no placement reaches can't be covered anyway, so only the reachable ones are
+        // filled. Leaving the others out doesn't change how many cells stay empty in a solution.
+        let reachable = placements
+            .iter()
+            .fold(B::EMPTY, |cells, &placement| cells | placement);
+        let free = (reachable & !initial_board).count_ones();
+
+        let mut area = 0;
+        for piece in 0..pieces {
+            // A piece with nowhere to go means there are no solutions
+            let Some(placement) =
+                placements[placement_indices[piece]..placement_indices[piece + 1]].first()
+            else {
+                return Ok(());
+            };
+            area += placement.count_ones();
+        }
+        let Some(spare) = free.checked_sub(area) else {
+            return Ok(());
+        };
+
+        let mut covering = vec![Vec::new(); B::BITS as usize];
+        for piece in 0..pieces {
+            let range = placement_indices[piece]..placement_indices[piece + 1];
+            for (index, &placement) in placements
+                .iter()
+                .enumerate()
+                .take(range.end)
+                .skip(range.start)
+            {
+                for cell in placement.ones() {
+                    covering[cell as usize].push((piece, index));
+                }
+            }
+        }
+
+        let mut search = Search {
+            placements,
+            covering,
+            reachable,
+            watchdog,
+            stats,
+            on_solution,
+            solution: vec![UNPLACED; pieces],
+        };
+        // Whether the callback stopped the search or it ran out of options, it's over
+        let _ = search.visit(initial_board, spare, 0)?;
+        Ok(())
+    }
+}
+
+struct Search<'a, 'w, B, S, F> {
+    placements: &'a [B],
+    // The `(piece, placement index)` pairs covering each cell
+    covering: Vec<Vec<(usize, usize)>>,
+    reachable: B,
+    watchdog: &'a mut Watchdog<'w>,
+    stats: &'a mut S,
+    on_solution: &'a mut F,
+    // The placement index of each piece, or `UNPLACED`
+    solution: Vec<usize>,
+}
+
+impl<B: BitBoard, S: Stats, F: FnMut(&[usize]) -> ControlFlow<()>> Search<'_, '_, B, S, F> {
+    // `board` has every covered cell set, along with the cells chosen to stay empty, of which
+    // `spare` more are allowed
+    fn visit(
+        &mut self,
+        board: B,
+        spare: u32,
+        depth: usize,
+    ) -> Result<ControlFlow<()>, Interrupted> {
+        self.watchdog.check(self.stats)?;
+
+        if depth == self.solution.len() {
+            self.stats.solution();
+            return Ok((self.on_solution)(&self.solution));
+        }
+
+        let open = self.reachable & !board;
+        if open.is_empty() {
+            return Ok(ControlFlow::Continue(()));
+        }
+        let cell = open.trailing_zeros();
+
+        for option in 0..self.covering[cell as usize].len() {
+            let (piece, index) = self.covering[cell as usize][option];
+            if self.solution[piece] != UNPLACED {
+                continue;
+            }
+
+            let placement = self.placements[index];
+            self.stats.attempt(piece);
+            if !placement.is_disjoint(board) {
+                continue;
+            }
+
+            self.solution[piece] = index;
+            self.stats.place(piece, index);
+            self.stats.depth(depth + 1);
+            let flow = self.visit(board | placement, spare, depth + 1)?;
+            self.stats.backtrack(piece);
+            self.solution[piece] = UNPLACED;
+
+            if flow.is_break() {
+                return Ok(flow);
+            }
+        }
+
+        if spare > 0 {
+            return self.visit(board | B::bit(cell), spare - 1, depth);
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+}
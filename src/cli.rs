@@ -0,0 +1,723 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{
+    backtrack::Order,
+    date::{Date, Month},
+    entity::Point,
+    render::{Format, Style},
+    variant::{self, Variant},
+};
+
+pub const USAGE: &str = "\
+Usage: date-puzzle [COMMAND] [MONTH DAY] [OPTIONS]
+
+Solves the puzzle for the given date, or for today's local date if none is given.
+
+Commands:
+  solve            Print a solution (the default)
+  count            Print only the number of solutions
+  year             Solve every date on the board and report the unsolvable ones
+  unique           List the dates with exactly one solution
+  hardest          Rank the dates by how few solutions they have
+  heatmap          Print a grid of solution counts for every date (or one --month)
+  bench            Time the first solution of every date on the board
+  list-tiles       Print each tile's shape, size and number of orientations
+  by-cell          Group every solution by the tile covering --cell, with an example
+                   of each
+  hint             Reveal where one more tile goes, given the --placed pieces
+  calendar         Write an iCalendar file with a solution for each day of --year,
+                   or a CSV of solution counts with --csv
+  gen              Search for sets of tiles from --pool that solve every date
+  serve            Answer `GET /solve?month=10&day=13` with JSON on --port
+  repl             Read commands like `solve oct 13` from a prompt, reusing the setup
+  verify FILE      Check a solution given as a grid of piece letters (with `#` for
+                   the uncovered cells) or as lines of `LABEL: (x,y) (x,y) ...`
+  solve-partial FILE
+                   Finish a board drawn as a grid of piece letters, with `.` for the
+                   empty cells and `#` for the date (used when no date is given)
+
+Arguments:
+  MONTH            Month name (e.g. october or oct) or number (1-12)
+  DAY              Day of the month (1-31)
+
+Options:
+  --month MONTH    Month name or number
+  --day DAY        Day of the month
+  --year YEAR      The year, used to check whether February 29 exists. For `calendar`,
+                   the year to cover (default: the current year)
+  --all            Print every solution instead of just the first
+  --limit N        With --all, stop searching after N solutions. With `gen`, stop after
+                   N tile sets (default: 1)
+  --from DATE      Solve every date from DATE (e.g. oct-1) through --to
+  --to DATE        The last date to solve
+  --wrap           Let --to come before --from, carrying on into the next year
+  --columns N      With --all or --from, print up to N solutions side by side, as many
+                   as fit in $COLUMNS when it's set
+  --shuffle        Randomize the search order to find varied solutions
+  --seed N         Seed for --shuffle, printed when chosen randomly
+  --timeout TIME   Give up searching after TIME (e.g. 30s, 500ms, 2m)
+  --threads N      Worker threads for multi-date commands (default: one per CPU)
+  --quiet          Don't show progress on stderr while solving many dates
+  --animate        Redraw the board in the terminal as the search places pieces. With
+                   --format png and --output, write the search as an animated PNG
+  --animate-speed TIME
+                   With --animate, the pause after each frame, or how long each frame
+                   of an image is shown (default: 50ms)
+  --fps N          With --animate in the terminal, draw at most N frames per second
+  --forward-only   With --animate, only show the steps which place pieces
+  --max-frames N   With --animate and --format png, stop adding frames after N, besides
+                   the last one showing the end of the search (default: 1000)
+  --stats          Print search statistics to stderr after solving
+  --order ORDER    Search strategy: static (default), mrv, cell, or dlx (which needs
+                   the `dlx` feature)
+  --one-sided      Only rotate the tiles, for pieces which can't be flipped over
+  --force          Allow dates that aren't in the calendar, like February 30
+  --stdin          Solve each date read from stdin, one `MONTH DAY` per line. Blank
+                   lines and `#` comments are skipped
+  --jsonl          With --stdin, print one JSON object per input line
+  --output PATH    Write each date's solution to a file instead of stdout. PATH is a
+                   directory, or a template like `out/{month}-{day}.txt` (`{date}`
+                   is also filled in). With `gen`, the directory for the tile sets,
+                   and with `calendar`, the file to write
+  --pool PATH      For `gen`, the candidate tiles (default: every free polyomino of
+                   sizes 5 and 6)
+  --budget N       For `gen`, placement attempts allowed per date before a set is
+                   rejected (default: 1000000000)
+  --port N         For `serve`, the local port to listen on (default: 8080)
+  --iterations N   For `bench`, solve each date N times and average (default: 1)
+  --count-distinct For `count`, also count the solutions left once copies of each other
+                   under a symmetry of the board are counted as one
+  --csv            For `bench`, print `date,order,microseconds,attempts` rows instead of a
+                   table. For `calendar`, print `date,solutions,first_solution` rows instead
+  --cell X,Y       For `by-cell`, the board coordinates of the cell to look at
+  --placed PATH    For `hint`, the pieces already placed, in the same forms `verify`
+                   accepts; the last piece is the one blamed for a dead end
+  --variant NAME   Which edition of the puzzle to solve (default: classic)
+  --exclude-tile N Leave tile N out of the puzzle, allowing as many extra cells to stay
+                   uncovered as it would have covered (repeatable)
+  --board PATH     Load the board from an ASCII drawing, where `#` marks a cell, with up
+                   to 128 cells. The drawing may be followed by `[labels]` and a line of
+                   `X,Y LABEL` for each cell showing a month or day, or else the cells are
+                   labeled like the variant's board
+  --tiles PATH     Load the tiles from ASCII drawings separated by blank lines. A
+                   drawing may start with `[one-sided]` or `[fixed]` to stop that tile
+                   from being flipped over or turned. Lines starting with `//` are
+                   comments
+  --format FORMAT  Output format: ascii (default), unicode, grid, json, coords, svg,
+                   html, markdown, or markdown-grid. `grid` (or `compact`) draws one
+                   character per cell, in the form `verify` reads, and is the default for
+                   `calendar`. `markdown-grid` puts it in a Markdown code block
+  --coords         Shorthand for --format coords, listing each tile's cells
+  --color WHEN     Paint each tile in its own color in the text formats: auto (default),
+                   always, or never. `auto` only uses colors on a terminal, and not when
+                   NO_COLOR is set
+  --scale N        With --format png, the pixels per cell (default: 32). The png format
+                   needs the `png` feature
+  --blank-tiles    Leave covered cells empty in the text formats, rather than marking
+                   each with its tile's letter
+  --legend         Follow each board in the text formats with every tile's letter over
+                   its shape
+  --json-errors    Report errors on stderr as JSON objects with a `kind` field
+  -h, --help       Print this message
+
+Exit status:
+  0  Success
+  1  No solution exists
+  2  Invalid input
+  3  The search timed out
+  4  A solution given to `verify`, `hint` or `solve-partial` is not valid
+  5  An internal error, which is a bug";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Solve,
+    Count,
+    Year,
+    Verify,
+    SolvePartial,
+    Heatmap,
+    Repl,
+    Hint,
+    ByCell,
+    Bench,
+    ListTiles,
+    Serve,
+    Hardest,
+    Unique,
+    Gen,
+    Calendar,
+}
+
+pub struct Args {
+    pub command: Command,
+    pub month: Option<Month>,
+    pub day: Option<i32>,
+    pub year: Option<i32>,
+    pub all: bool,
+    pub limit: Option<usize>,
+    pub columns: Option<usize>,
+    pub range: Option<(Date, Date)>,
+    pub wrap: bool,
+    pub shuffle: bool,
+    pub seed: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub threads: Option<usize>,
+    pub stats: bool,
+    pub animate: bool,
+    pub animate_speed: Option<Duration>,
+    pub fps: Option<u32>,
+    pub forward_only: bool,
+    pub max_frames: Option<usize>,
+    pub quiet: bool,
+    pub force: bool,
+    pub one_sided: bool,
+    pub order: Order,
+    pub solution: Option<PathBuf>,
+    pub placed: Option<PathBuf>,
+    pub cell: Option<Point>,
+    pub stdin: bool,
+    pub jsonl: bool,
+    pub output: Option<String>,
+    pub port: Option<u16>,
+    pub pool: Option<PathBuf>,
+    pub budget: Option<u64>,
+    pub iterations: Option<usize>,
+    pub csv: bool,
+    pub count_distinct: bool,
+    pub variant: Option<&'static Variant>,
+    pub board: Option<PathBuf>,
+    pub tiles: Option<PathBuf>,
+    pub exclude_tiles: Vec<usize>,
+    pub format: Format,
+}
+
+#[derive(Clone, Copy)]
+enum Color {
+    Always,
+    Never,
+    Auto,
+}
+
+impl Color {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "always" => Ok(Color::Always),
+            "never" => Ok(Color::Never),
+            "auto" => Ok(Color::Auto),
+            _ => Err(format!(
+                "invalid color choice `{s}`, expected always, never, or auto"
+            )),
+        }
+    }
+
+    // Asking for colors outright wins over `NO_COLOR`, which only turns off the automatic ones
+    fn enabled(self) -> bool {
+        match self {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => {
+                io::stdout().is_terminal()
+                    && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+pub enum Parsed {
+    Run(Box<Args>),
+    Help,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Parsed, String> {
+    let mut args = args.into_iter().peekable();
+    let command = match args.peek().map(String::as_str) {
+        Some("solve") => Some(Command::Solve),
+        Some("count") => Some(Command::Count),
+        Some("year") => Some(Command::Year),
+        Some("verify") => Some(Command::Verify),
+        Some("solve-partial") => Some(Command::SolvePartial),
+        Some("heatmap") => Some(Command::Heatmap),
+        Some("repl") => Some(Command::Repl),
+        Some("hint") => Some(Command::Hint),
+        Some("by-cell") => Some(Command::ByCell),
+        Some("bench") => Some(Command::Bench),
+        Some("list-tiles") => Some(Command::ListTiles),
+        Some("serve") => Some(Command::Serve),
+        Some("gen") => Some(Command::Gen),
+        Some("calendar") => Some(Command::Calendar),
+        Some("hardest") => Some(Command::Hardest),
+        Some("unique") => Some(Command::Unique),
+        _ => None,
+    };
+    if command.is_some() {
+        args.next();
+    }
+    let command = command.unwrap_or(Command::Solve);
+
+    let mut month = None;
+    let mut day = None;
+    let mut year = None;
+    let mut all = false;
+    let mut limit = None;
+    let mut columns = None;
+    let mut from = None;
+    let mut to = None;
+    let mut wrap = false;
+    let mut shuffle = false;
+    let mut seed = None;
+    let mut timeout = None;
+    let mut threads = None;
+    let mut stats = false;
+    let mut animate = false;
+    let mut animate_speed = None;
+    let mut fps = None;
+    let mut forward_only = false;
+    let mut max_frames = None;
+    let mut quiet = false;
+    let mut force = false;
+    let mut one_sided = false;
+    let mut order = Order::Static;
+    let mut placed = None;
+    let mut cell = None;
+    let mut stdin = false;
+    let mut jsonl = false;
+    let mut output = None;
+    let mut port = None;
+    let mut pool = None;
+    let mut budget = None;
+    let mut iterations = None;
+    let mut csv = false;
+    let mut count_distinct = false;
+    let mut variant = None;
+    let mut board = None;
+    let mut tiles = None;
+    let mut exclude_tiles = Vec::new();
+    let mut format = None;
+    let mut color = Color::Auto;
+    let mut blank_tiles = false;
+    let mut legend = false;
+    let mut scale = None;
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "--month" => month = Some(parse_month(&next_value(&mut args, &arg)?)?),
+            "--day" => day = Some(parse_day(&next_value(&mut args, &arg)?)?),
+            "--year" => year = Some(parse_year(&next_value(&mut args, &arg)?)?),
+            "--all" => all = true,
+            "--limit" => limit = Some(parse_limit(&next_value(&mut args, &arg)?)?),
+            "--columns" => columns = Some(parse_columns(&next_value(&mut args, &arg)?)?),
+            "--from" => from = Some(parse_date(&next_value(&mut args, &arg)?)?),
+            "--to" => to = Some(parse_date(&next_value(&mut args, &arg)?)?),
+            "--wrap" => wrap = true,
+            "--shuffle" => shuffle = true,
+            "--seed" => seed = Some(parse_seed(&next_value(&mut args, &arg)?)?),
+            "--timeout" => timeout = Some(parse_duration(&next_value(&mut args, &arg)?)?),
+            "--threads" => threads = Some(parse_threads(&next_value(&mut args, &arg)?)?),
+            "--stats" => stats = true,
+            "--animate" => animate = true,
+            "--animate-speed" => {
+                animate_speed = Some(parse_duration(&next_value(&mut args, &arg)?)?)
+            }
+            "--fps" => fps = Some(parse_fps(&next_value(&mut args, &arg)?)?),
+            "--forward-only" => forward_only = true,
+            "--max-frames" => max_frames = Some(parse_max_frames(&next_value(&mut args, &arg)?)?),
+            "--quiet" => quiet = true,
+            "--force" => force = true,
+            "--one-sided" => one_sided = true,
+            "--order" => order = Order::parse(&next_value(&mut args, &arg)?)?,
+            // Handled in `main` before parsing so it applies to parse errors too
+            "--json-errors" => (),
+            "--stdin" => stdin = true,
+            "--jsonl" => jsonl = true,
+            "--output" => output = Some(next_value(&mut args, &arg)?),
+            "--pool" => pool = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--budget" => budget = Some(parse_budget(&next_value(&mut args, &arg)?)?),
+            "--port" => port = Some(parse_port(&next_value(&mut args, &arg)?)?),
+            "--iterations" => iterations = Some(parse_iterations(&next_value(&mut args, &arg)?)?),
+            "--csv" => csv = true,
+            "--count-distinct" => count_distinct = true,
+            "--cell" => cell = Some(parse_cell(&next_value(&mut args, &arg)?)?),
+            "--placed" => placed = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--variant" => variant = Some(variant::find(&next_value(&mut args, &arg)?)?),
+            "--board" => board = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--tiles" => tiles = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--exclude-tile" => {
+                exclude_tiles.push(parse_tile_index(&next_value(&mut args, &arg)?)?)
+            }
+            "--format" => format = Some(Format::parse(&next_value(&mut args, &arg)?)?),
+            "--coords" => format = Some(Format::Coords),
+            "--color" => color = Color::parse(&next_value(&mut args, &arg)?)?,
+            "--blank-tiles" => blank_tiles = true,
+            "--legend" => legend = true,
+            "--scale" => scale = Some(parse_scale(&next_value(&mut args, &arg)?)?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => positional.push(arg),
+        }
+    }
+
+    // A calendar's descriptions are read in a narrow box, where the boxed drawings don't fit
+    let mut format = format.unwrap_or(match command {
+        Command::Calendar => Format::Grid(Style::PLAIN),
+        _ => Format::Ascii(Style::PLAIN),
+    });
+
+    if limit.is_some() && !all && command != Command::Gen {
+        return Err("`--limit` can only be used with `--all` or `gen`".to_owned());
+    }
+
+    if seed.is_some() && !shuffle {
+        return Err("`--seed` can only be used with `--shuffle`".to_owned());
+    }
+
+    if placed.is_some() && command != Command::Hint {
+        return Err("`--placed` can only be used with `hint`".to_owned());
+    }
+
+    if cell.is_some() != (command == Command::ByCell) {
+        return Err("`--cell` is required by `by-cell`, and can only be used with it".to_owned());
+    }
+
+    if (animate_speed.is_some() || fps.is_some() || forward_only || max_frames.is_some())
+        && !animate
+    {
+        return Err(
+            "`--animate-speed`, `--fps`, `--forward-only` and `--max-frames` can only be used \
+             with `--animate`"
+                .to_owned(),
+        );
+    }
+
+    // Text is drawn over in the terminal, while an image has to go to a file
+    let image = format.extension() == "png";
+    if animate
+        && (command != Command::Solve
+            || all
+            || stdin
+            || from.is_some()
+            || !(format.is_text() && output.is_none() || image && output.is_some()))
+    {
+        return Err(
+            "`--animate` can only be used to solve a single date with a text format, or with \
+             `--format png` and `--output`"
+                .to_owned(),
+        );
+    }
+
+    if image && fps.is_some() {
+        return Err("`--fps` can only be used when animating in the terminal".to_owned());
+    }
+
+    if max_frames.is_some() && !image {
+        return Err("`--max-frames` can only be used with `--format png`".to_owned());
+    }
+
+    if let Some(scale) = scale {
+        format = format
+            .with_scale(scale)
+            .ok_or("`--scale` can only be used with `--format png`")?;
+    }
+
+    // An image holds one solution, so it can't be mixed in with other output
+    if image && (command != Command::Solve || all || stdin || from.is_some()) {
+        return Err("`--format png` can only be used to solve a single date".to_owned());
+    }
+
+    if columns.is_some()
+        && (command != Command::Solve
+            || !(all || from.is_some())
+            || stdin
+            || output.is_some()
+            || !format.is_text())
+    {
+        return Err(
+            "`--columns` can only be used with `--all` or `--from`, printing a text format"
+                .to_owned(),
+        );
+    }
+
+    if jsonl && !stdin {
+        return Err("`--jsonl` can only be used with `--stdin`".to_owned());
+    }
+
+    if stdin && (command != Command::Solve || all || from.is_some() || output.is_some()) {
+        return Err(
+            "`--stdin` can only be used to solve dates without `--all`, `--from` or `--output`"
+                .to_owned(),
+        );
+    }
+
+    if stdin && (month.is_some() || day.is_some()) {
+        return Err("dates can't be given along with `--stdin`".to_owned());
+    }
+
+    if output.is_some()
+        && (command != Command::Solve || all)
+        && !matches!(command, Command::Gen | Command::Calendar)
+    {
+        return Err("`--output` can only be used to solve dates without `--all`".to_owned());
+    }
+
+    if (pool.is_some() || budget.is_some()) && command != Command::Gen {
+        return Err("`--pool` and `--budget` can only be used with `gen`".to_owned());
+    }
+
+    if port.is_some() && command != Command::Serve {
+        return Err("`--port` can only be used with `serve`".to_owned());
+    }
+
+    if iterations.is_some() && command != Command::Bench {
+        return Err("`--iterations` can only be used with `bench`".to_owned());
+    }
+
+    if csv && !matches!(command, Command::Bench | Command::Calendar) {
+        return Err("`--csv` can only be used with `bench` or `calendar`".to_owned());
+    }
+
+    if count_distinct && command != Command::Count {
+        return Err("`--count-distinct` can only be used with `count`".to_owned());
+    }
+
+    let range = match (from, to) {
+        (Some(from), Some(to)) => Some((from, to)),
+        (None, None) => None,
+        _ => return Err("`--from` and `--to` must be used together".to_owned()),
+    };
+    if wrap && range.is_none() {
+        return Err("`--wrap` can only be used with `--from` and `--to`".to_owned());
+    }
+
+    // The file to check or finish comes before the date
+    let solution = match command {
+        Command::Verify if positional.is_empty() => {
+            return Err("`verify` requires the path of a solution to check".to_owned())
+        }
+        Command::SolvePartial if positional.is_empty() => {
+            return Err("`solve-partial` requires the path of a board to finish".to_owned())
+        }
+        Command::Verify | Command::SolvePartial => Some(PathBuf::from(positional.remove(0))),
+        _ => None,
+    };
+
+    if command == Command::Calendar && (!positional.is_empty() || month.is_some() || day.is_some())
+    {
+        return Err("`calendar` covers the whole --year, so it doesn't take a date".to_owned());
+    }
+
+    match positional.as_slice() {
+        [] => (),
+        _ if stdin => return Err("dates can't be given along with `--stdin`".to_owned()),
+        [m, d] if month.is_none() && day.is_none() => {
+            month = Some(parse_month(m)?);
+            day = Some(parse_day(d)?);
+        }
+        [_, _] => return Err("the date was given both positionally and with options".to_owned()),
+        _ => return Err("expected a month followed by a day".to_owned()),
+    }
+
+    Ok(Parsed::Run(Box::new(Args {
+        command,
+        month,
+        day,
+        year,
+        all,
+        limit,
+        columns,
+        range,
+        wrap,
+        shuffle,
+        seed,
+        timeout,
+        threads,
+        stats,
+        animate,
+        animate_speed,
+        fps,
+        forward_only,
+        max_frames,
+        quiet,
+        force,
+        one_sided,
+        order,
+        solution,
+        placed,
+        cell,
+        stdin,
+        jsonl,
+        output,
+        port,
+        pool,
+        budget,
+        iterations,
+        csv,
+        count_distinct,
+        variant,
+        board,
+        tiles,
+        exclude_tiles,
+        format: format.with_style(Style {
+            color: color.enabled(),
+            blank: blank_tiles,
+            legend,
+        }),
+    })))
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("option `{option}` requires a value"))
+}
+
+// A date given as `MONTH DAY` or `MONTH-DAY`, as read by `--stdin`
+pub fn parse_date_line(line: &str) -> Result<Date, String> {
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [month, day] => Ok(Date {
+            year: None,
+            month: parse_month(month)?,
+            day: parse_day(day)?,
+        }),
+        [date] => parse_date(date),
+        _ => Err("expected a month followed by a day".to_owned()),
+    }
+}
+
+pub fn parse_month(s: &str) -> Result<Month, String> {
+    s.parse::<Month>().map_err(|error| error.to_string())
+}
+
+pub fn parse_day(s: &str) -> Result<i32, String> {
+    match s.parse::<i32>() {
+        Ok(day) if (1..=31).contains(&day) => Ok(day),
+        _ => Err(format!("invalid day `{s}`, expected a number from 1 to 31")),
+    }
+}
+
+pub fn parse_year(s: &str) -> Result<i32, String> {
+    s.parse::<i32>()
+        .map_err(|_| format!("invalid year `{s}`, expected a number"))
+}
+
+fn parse_limit(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(limit) if limit > 0 => Ok(limit),
+        _ => Err(format!("invalid limit `{s}`, expected a positive number")),
+    }
+}
+
+fn parse_columns(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(columns) if columns > 0 => Ok(columns),
+        _ => Err(format!(
+            "invalid column count `{s}`, expected a positive number"
+        )),
+    }
+}
+
+fn parse_budget(s: &str) -> Result<u64, String> {
+    match s.parse::<u64>() {
+        Ok(budget) if budget > 0 => Ok(budget),
+        _ => Err(format!("invalid budget `{s}`, expected a positive number")),
+    }
+}
+
+fn parse_fps(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(fps) if fps > 0 => Ok(fps),
+        _ => Err(format!(
+            "invalid frame rate `{s}`, expected a positive number"
+        )),
+    }
+}
+
+fn parse_max_frames(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(frames) if frames > 0 => Ok(frames),
+        _ => Err(format!(
+            "invalid frame count `{s}`, expected a positive number"
+        )),
+    }
+}
+
+fn parse_scale(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(scale) if (8..=256).contains(&scale) => Ok(scale),
+        _ => Err(format!(
+            "invalid scale `{s}`, expected a number of pixels from 8 to 256"
+        )),
+    }
+}
+
+fn parse_port(s: &str) -> Result<u16, String> {
+    s.parse::<u16>()
+        .map_err(|_| format!("invalid port `{s}`, expected a number from 0 to 65535"))
+}
+
+fn parse_iterations(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(iterations) if iterations > 0 => Ok(iterations),
+        _ => Err(format!(
+            "invalid iteration count `{s}`, expected a positive number"
+        )),
+    }
+}
+
+fn parse_cell(s: &str) -> Result<Point, String> {
+    s.split_once(',')
+        .and_then(|(x, y)| {
+            Some(Point {
+                x: x.trim().parse().ok()?,
+                y: y.trim().parse().ok()?,
+            })
+        })
+        .ok_or_else(|| format!("invalid cell `{s}`, expected X,Y such as `0,6`"))
+}
+
+fn parse_tile_index(s: &str) -> Result<usize, String> {
+    s.parse::<usize>()
+        .map_err(|_| format!("invalid tile index `{s}`, expected a non-negative number"))
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    Date::parse_month_day(s)
+        .ok_or_else(|| format!("invalid date `{s}`, expected MONTH-DAY such as `oct-13`"))
+}
+
+fn parse_seed(s: &str) -> Result<u64, String> {
+    s.parse::<u64>()
+        .map_err(|_| format!("invalid seed `{s}`, expected a non-negative integer"))
+}
+
+// Accepts a number followed by `ms`, `s`, or `m`, with a bare number meaning seconds
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{s}`, expected something like `30s` or `500ms`");
+
+    let (num, unit_ms) = if let Some(num) = s.strip_suffix("ms") {
+        (num, 1)
+    } else if let Some(num) = s.strip_suffix('s') {
+        (num, 1000)
+    } else if let Some(num) = s.strip_suffix('m') {
+        (num, 60_000)
+    } else {
+        (s, 1000)
+    };
+
+    let num = num.parse::<u64>().map_err(|_| invalid())?;
+    num.checked_mul(unit_ms)
+        .map(Duration::from_millis)
+        .ok_or_else(invalid)
+}
+
+fn parse_threads(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(threads) if threads > 0 => Ok(threads),
+        _ => Err(format!(
+            "invalid thread count `{s}`, expected a positive number"
+        )),
+    }
+}
@@ -0,0 +1,478 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Month {
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+impl Month {
+    pub const ALL: [Month; 12] = [
+        Month::January,
+        Month::February,
+        Month::March,
+        Month::April,
+        Month::May,
+        Month::June,
+        Month::July,
+        Month::August,
+        Month::September,
+        Month::October,
+        Month::November,
+        Month::December,
+    ];
+
+    // One-based, so January is 1
+    pub fn from_number(num: i32) -> Option<Self> {
+        if (1..=12).contains(&num) {
+            Some(Self::ALL[num as usize - 1])
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Month::January => "January",
+            Month::February => "February",
+            Month::March => "March",
+            Month::April => "April",
+            Month::May => "May",
+            Month::June => "June",
+            Month::July => "July",
+            Month::August => "August",
+            Month::September => "September",
+            Month::October => "October",
+            Month::November => "November",
+            Month::December => "December",
+        }
+    }
+
+    // The number of days in the month. February is given 29 days when the year is unknown, since
+    // that's the most permissive choice.
+    pub fn days(&self, year: Option<i32>) -> i32 {
+        match self {
+            Month::February if year.is_some_and(|year| !is_leap_year(year)) => 28,
+            Month::February => 29,
+            Month::April | Month::June | Month::September | Month::November => 30,
+            _ => 31,
+        }
+    }
+
+    pub fn abbreviation(&self) -> &'static str {
+        &self.name()[..3]
+    }
+}
+
+impl Display for Month {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseMonthError(String);
+
+impl Display for ParseMonthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid month `{}`, expected a full name (e.g. october), a three-letter abbreviation (e.g. oct), or a number from 1 to 12",
+            self.0
+        )
+    }
+}
+
+// Accepts the full name of the month, its three-letter abbreviation (both case-insensitive), or
+// its one-based number. Other prefixes of the name are rejected, since some of them (such as "ju")
+// are ambiguous.
+impl FromStr for Month {
+    type Err = ParseMonthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let month = match s.parse::<i32>() {
+            Ok(num) => Self::from_number(num),
+            Err(..) => Self::ALL.into_iter().find(|month| {
+                month.name().eq_ignore_ascii_case(s) || month.abbreviation().eq_ignore_ascii_case(s)
+            }),
+        };
+
+        month.ok_or_else(|| ParseMonthError(s.to_owned()))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Date {
+    // Set when any part of the date came from today's, or when a year was asked for, such as
+    // with `--year` or by `year_dates`. Without one the date stands for that day in any year, so
+    // February 29 counts as real.
+    pub year: Option<i32>,
+    pub month: Month,
+    pub day: i32,
+}
+
+impl Date {
+    // Converts a number of days since 1970-01-01 to a civil date in the proleptic Gregorian
+    // calendar. See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months are counted starting from March here so that leap days fall at the end
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: Some(year as i32),
+            month: Month::from_number(month as i32).unwrap(),
+            day: day as i32,
+        }
+    }
+
+    // The current date according to the system clock, in the local time zone
+    pub fn today() -> Self {
+        let secs = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(error) => -(error.duration().as_secs() as i64),
+        };
+
+        Self::from_days_since_epoch((secs + utc_offset(secs)).div_euclid(86_400))
+    }
+
+    // Formats the date as YYYY-MM-DD, or just MM-DD if the year isn't known
+    pub fn numeric(&self) -> String {
+        let month = self.month as i32 + 1;
+        match self.year {
+            Some(year) => format!("{year:04}-{month:02}-{:02}", self.day),
+            None => format!("{month:02}-{:02}", self.day),
+        }
+    }
+
+    // Checks that the date exists in the calendar, taking leap years into account when the year is
+    // known. The board has a cell for every day from 1 to 31 in every month, so dates like
+    // February 30 can be solved but aren't real.
+    pub fn validate(&self) -> Result<(), String> {
+        if (1..=self.month.days(self.year)).contains(&self.day) {
+            return Ok(());
+        }
+
+        match self.year {
+            Some(year) if self.month == Month::February && self.day == 29 => Err(format!(
+                "{self} is not a real date, since {year} is not a leap year"
+            )),
+            _ => Err(format!("{self} is not a real date")),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    // Every month and day combination on the board, from January 1 through December 31
+    pub fn board_dates() -> impl Iterator<Item = Date> {
+        Month::ALL.into_iter().flat_map(|month| {
+            (1..=31).map(move |day| Date {
+                year: None,
+                month,
+                day,
+            })
+        })
+    }
+
+    // Every real date in the given year, from January 1 through December 31
+    pub fn year_dates(year: i32) -> impl Iterator<Item = Date> {
+        Month::ALL.into_iter().flat_map(move |month| {
+            (1..=month.days(Some(year))).map(move |day| Date {
+                year: Some(year),
+                month,
+                day,
+            })
+        })
+    }
+
+    // The day after this one, moving into the next month or year as needed
+    pub fn next_day(&self) -> Self {
+        if self.day < self.month.days(self.year) {
+            return Self {
+                day: self.day + 1,
+                ..*self
+            };
+        }
+
+        match Month::from_number(self.month as i32 + 2) {
+            Some(month) => Self {
+                year: self.year,
+                month,
+                day: 1,
+            },
+            None => Self {
+                year: self.year.map(|year| year + 1),
+                month: Month::January,
+                day: 1,
+            },
+        }
+    }
+
+    // The board dates from `from` to `to` inclusive. Returns `None` if `to` comes before `from`
+    // in the year, unless `wrap` is set, in which case the range carries on past December 31
+    // into the next year.
+    pub fn range(from: Date, to: Date, wrap: bool) -> Option<Vec<Date>> {
+        let position = |date: Date| (date.month as usize) * 31 + date.day as usize - 1;
+        let (start, end) = (position(from), position(to));
+        let dates = Self::board_dates().collect::<Vec<_>>();
+
+        if start <= end {
+            Some(dates[start..=end].to_vec())
+        } else if wrap {
+            Some([&dates[start..], &dates[..=end]].concat())
+        } else {
+            None
+        }
+    }
+
+    // Parses dates of the form MONTH-DAY, such as `oct-13` or `10-13`
+    pub fn parse_month_day(s: &str) -> Option<Self> {
+        let (month, day) = s.rsplit_once('-')?;
+        let day = day
+            .parse::<i32>()
+            .ok()
+            .filter(|day| (1..=31).contains(day))?;
+
+        Some(Self {
+            year: None,
+            month: month.parse().ok()?,
+            day,
+        })
+    }
+
+    // Fills in whichever of the month and day weren't explicitly requested from `today`. The year
+    // is only kept if some part of the date actually came from `today`.
+    pub fn resolve(month: Option<Month>, day: Option<i32>, today: Date) -> Self {
+        match (month, day) {
+            (Some(month), Some(day)) => Self {
+                year: None,
+                month,
+                day,
+            },
+            (month, day) => Self {
+                year: today.year,
+                month: month.unwrap_or(today.month),
+                day: day.unwrap_or(today.day),
+            },
+        }
+    }
+}
+
+// How many seconds the local time zone is ahead of UTC at the given time, which honours `TZ`
+#[cfg(unix)]
+fn utc_offset(secs: i64) -> i64 {
+    let time = secs as libc::time_t;
+    // SAFETY: `localtime_r` only writes to the `tm` it's given, and an all-zero `tm` is valid
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff as i64
+    }
+}
+
+// Other platforms use UTC
+#[cfg(not(unix))]
+fn utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+// Formats as the month name followed by the day, such as "October 13"
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: Month, day: i32) -> Date {
+        Date {
+            year: None,
+            month,
+            day,
+        }
+    }
+
+    #[test]
+    fn months_parse_from_names_and_numbers() {
+        assert_eq!("october".parse(), Ok(Month::October));
+        assert_eq!("OCT".parse(), Ok(Month::October));
+        assert_eq!("Oct".parse(), Ok(Month::October));
+        assert_eq!("10".parse(), Ok(Month::October));
+        assert_eq!("1".parse(), Ok(Month::January));
+        assert_eq!("12".parse(), Ok(Month::December));
+        assert_eq!("jun".parse(), Ok(Month::June));
+        assert_eq!("jul".parse(), Ok(Month::July));
+    }
+
+    #[test]
+    fn ambiguous_and_unknown_months_are_rejected() {
+        for s in ["ju", "j", "octo", "", "0", "13", "-1", "smarch"] {
+            assert_eq!(
+                s.parse::<Month>(),
+                Err(ParseMonthError(s.to_owned())),
+                "{s}"
+            );
+        }
+        let message = "ju".parse::<Month>().unwrap_err().to_string();
+        assert!(message.starts_with("invalid month `ju`"), "{message}");
+    }
+
+    #[test]
+    fn months_display_their_names() {
+        assert_eq!(Month::October.to_string(), "October");
+        assert_eq!(date(Month::October, 13).to_string(), "October 13");
+        for month in Month::ALL {
+            assert_eq!(month.to_string().parse(), Ok(month));
+        }
+    }
+
+    #[test]
+    fn impossible_dates_are_invalid() {
+        for (month, day) in [
+            (Month::February, 30),
+            (Month::February, 31),
+            (Month::April, 31),
+            (Month::June, 31),
+            (Month::September, 31),
+            (Month::November, 31),
+        ] {
+            let error = date(month, day).validate().unwrap_err();
+            assert_eq!(error, format!("{month} {day} is not a real date"));
+        }
+        assert!(date(Month::January, 31).is_valid());
+        assert!(date(Month::April, 30).is_valid());
+    }
+
+    #[test]
+    fn february_29_depends_on_the_year() {
+        let leap_day = |year| Date {
+            year,
+            ..date(Month::February, 29)
+        };
+        assert!(leap_day(None).is_valid());
+        assert!(leap_day(Some(2024)).is_valid());
+        assert!(leap_day(Some(2000)).is_valid());
+        assert_eq!(
+            leap_day(Some(2023)).validate(),
+            Err("February 29 is not a real date, since 2023 is not a leap year".to_owned())
+        );
+        assert!(!leap_day(Some(1900)).is_valid());
+    }
+
+    #[test]
+    fn days_since_epoch() {
+        let at = |days| Date::from_days_since_epoch(days);
+        assert_eq!(
+            at(0),
+            Date {
+                year: Some(1970),
+                ..date(Month::January, 1)
+            }
+        );
+        assert_eq!(
+            at(19_782),
+            Date {
+                year: Some(2024),
+                ..date(Month::February, 29)
+            }
+        );
+        assert_eq!(
+            at(-1),
+            Date {
+                year: Some(1969),
+                ..date(Month::December, 31)
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_fills_in_from_today() {
+        let today = Date {
+            year: Some(2024),
+            ..date(Month::October, 13)
+        };
+        assert_eq!(
+            Date::resolve(Some(Month::March), Some(4), today),
+            date(Month::March, 4)
+        );
+        assert_eq!(
+            Date::resolve(None, Some(4), today),
+            Date {
+                year: Some(2024),
+                ..date(Month::October, 4)
+            }
+        );
+        assert_eq!(Date::resolve(None, None, today), today);
+    }
+
+    #[test]
+    fn range_within_a_year() {
+        let dates = Date::range(date(Month::October, 30), date(Month::November, 2), false).unwrap();
+        let days = dates
+            .iter()
+            .map(|date| (date.month, date.day))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            days,
+            [
+                (Month::October, 30),
+                (Month::October, 31),
+                (Month::November, 1),
+                (Month::November, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn reversed_range_is_rejected_without_wrap() {
+        assert!(Date::range(date(Month::October, 13), date(Month::October, 12), false).is_none());
+        assert!(Date::range(date(Month::December, 20), date(Month::January, 5), false).is_none());
+    }
+
+    #[test]
+    fn reversed_range_wraps_into_the_next_year() {
+        let dates = Date::range(date(Month::December, 20), date(Month::January, 5), true).unwrap();
+        assert_eq!(dates.len(), 12 + 5);
+        assert_eq!(dates.first(), Some(&date(Month::December, 20)));
+        assert_eq!(dates.last(), Some(&date(Month::January, 5)));
+    }
+}
@@ -0,0 +1,270 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    backtrack::{Interrupted, Stats, Strategy, Watchdog},
+    bitboard::BitBoard,
+};
+
+// Marks a piece which isn't on the board yet
+const UNPLACED: usize = usize::MAX;
+
+// The root of the list of columns still to be covered
+const ROOT: usize = 0;
+
+// Treats the puzzle as an exact cover problem, with a column for every piece and every free cell,
+// and solves it with Knuth's Algorithm X using dancing links. The column with the fewest rows left
+// is covered next, which is sometimes a piece and sometimes a cell. When the tiles cover fewer
+// cells than are free, the cells become optional columns which may be covered at most once.
+pub struct Dlx;
+
+impl Strategy for Dlx {
+    fn search<B: BitBoard>(
+        &self,
+        initial_board: B,
+        placements: &[B],
+        placement_indices: &[usize],
+        watchdog: &mut Watchdog<'_>,
+        stats: &mut impl Stats,
+        on_solution: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<(), Interrupted> {
+        let pieces = placement_indices.len() - 1;
+
+        // As in the cell order, only cells some placement reaches need columns
+        let reachable = placements
+            .iter()
+            .fold(B::EMPTY, |cells, &placement| cells | placement);
+        let free = (reachable & !initial_board).count_ones();
+        let area = (0..pieces)
+            .filter_map(|piece| {
+                placements[placement_indices[piece]..placement_indices[piece + 1]].first()
+            })
+            .map(|placement| placement.count_ones())
+            .sum::<u32>();
+        if area > free {
+            return Ok(());
+        }
+
+        let mut links = Links::new(pieces, reachable & !initial_board, area < free);
+        for piece in 0..pieces {
+            let range = placement_indices[piece]..placement_indices[piece + 1];
+            for (index, &placement) in placements
+                .iter()
+                .enumerate()
+                .take(range.end)
+                .skip(range.start)
+            {
+                links.add_row(piece, index, placement);
+            }
+        }
+
+        let mut search = Search {
+            links,
+            watchdog,
+            stats,
+            on_solution,
+            solution: vec![UNPLACED; pieces],
+        };
+        // Whether the callback stopped the search or it ran out of options, it's over
+        let _ = search.visit(0)?;
+        Ok(())
+    }
+}
+
+// The sparse matrix as a set of circular doubly linked lists. Node 0 is the root, followed by
+// one header per column, and then one node per one in the matrix.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    // The number of rows left in each column, indexed by header
+    size: Vec<usize>,
+    // The piece and placement index of each node's row
+    row: Vec<(usize, usize)>,
+    // The header of each cell's column, indexed by bit
+    cell_columns: Vec<usize>,
+}
+
+impl Links {
+    // Pieces always have to be placed, but free cells only have to be covered if `optional_cells`
+    // is false
+    fn new<B: BitBoard>(pieces: usize, cells: B, optional_cells: bool) -> Self {
+        let mut links = Self {
+            left: vec![ROOT],
+            right: vec![ROOT],
+            up: vec![ROOT],
+            down: vec![ROOT],
+            column: vec![ROOT],
+            size: vec![0],
+            row: vec![(UNPLACED, UNPLACED)],
+            cell_columns: vec![ROOT; B::BITS as usize],
+        };
+
+        for _ in 0..pieces {
+            links.add_column(true);
+        }
+        for bit in cells.ones() {
+            links.cell_columns[bit as usize] = links.add_column(!optional_cells);
+        }
+
+        links
+    }
+
+    fn push(&mut self, column: usize, row: (usize, usize)) -> usize {
+        let node = self.left.len();
+        self.left.push(node);
+        self.right.push(node);
+        self.up.push(node);
+        self.down.push(node);
+        self.column.push(column);
+        self.row.push(row);
+        node
+    }
+
+    // Adds an empty column, linked into the root's list only if it has to be covered
+    fn add_column(&mut self, required: bool) -> usize {
+        let header = self.push(self.left.len(), (UNPLACED, UNPLACED));
+        self.size.push(0);
+        if required {
+            self.left[header] = self.left[ROOT];
+            self.right[header] = ROOT;
+            self.right[self.left[ROOT]] = header;
+            self.left[ROOT] = header;
+        }
+        header
+    }
+
+    fn add_row<B: BitBoard>(&mut self, piece: usize, index: usize, placement: B) {
+        // Piece columns come straight after the root
+        let mut columns = vec![piece + 1];
+        columns.extend(
+            placement
+                .ones()
+                .map(|cell| self.cell_columns[cell as usize]),
+        );
+
+        let mut first = None;
+        for column in columns {
+            let node = self.push(column, (piece, index));
+            self.up[node] = self.up[column];
+            self.down[node] = column;
+            self.down[self.up[column]] = node;
+            self.up[column] = node;
+            self.size[column] += 1;
+
+            if let Some(first) = first {
+                self.left[node] = self.left[first];
+                self.right[node] = first;
+                self.right[self.left[first]] = node;
+                self.left[first] = node;
+            } else {
+                first = Some(node);
+            }
+        }
+    }
+
+    fn cover(&mut self, column: usize) {
+        self.right[self.left[column]] = self.right[column];
+        self.left[self.right[column]] = self.left[column];
+
+        let mut row = self.down[column];
+        while row != column {
+            let mut node = self.right[row];
+            while node != row {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    // Undoes `cover`, restoring the links in exactly the reverse order
+    fn uncover(&mut self, column: usize) {
+        let mut row = self.up[column];
+        while row != column {
+            let mut node = self.left[row];
+            while node != row {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+
+        self.right[self.left[column]] = column;
+        self.left[self.right[column]] = column;
+    }
+}
+
+struct Search<'a, 'w, S, F> {
+    links: Links,
+    watchdog: &'a mut Watchdog<'w>,
+    stats: &'a mut S,
+    on_solution: &'a mut F,
+    // The placement index of each piece, or `UNPLACED`
+    solution: Vec<usize>,
+}
+
+impl<S: Stats, F: FnMut(&[usize]) -> ControlFlow<()>> Search<'_, '_, S, F> {
+    fn visit(&mut self, depth: usize) -> Result<ControlFlow<()>, Interrupted> {
+        self.watchdog.check(self.stats)?;
+
+        let links = &self.links;
+        if links.right[ROOT] == ROOT {
+            self.stats.solution();
+            return Ok((self.on_solution)(&self.solution));
+        }
+
+        // The required column with the fewest rows left
+        let mut column = links.right[ROOT];
+        let mut header = links.right[column];
+        while header != ROOT {
+            if links.size[header] < links.size[column] {
+                column = header;
+            }
+            header = links.right[header];
+        }
+        if links.size[column] == 0 {
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        self.links.cover(column);
+        let mut row = self.links.down[column];
+        let mut flow = ControlFlow::Continue(());
+        while row != column {
+            let (piece, index) = self.links.row[row];
+            self.stats.attempt(piece);
+
+            let mut node = self.links.right[row];
+            while node != row {
+                self.links.cover(self.links.column[node]);
+                node = self.links.right[node];
+            }
+            self.solution[piece] = index;
+            self.stats.place(piece, index);
+            self.stats.depth(depth + 1);
+
+            flow = self.visit(depth + 1)?;
+
+            self.stats.backtrack(piece);
+            self.solution[piece] = UNPLACED;
+            let mut node = self.links.left[row];
+            while node != row {
+                self.links.uncover(self.links.column[node]);
+                node = self.links.left[node];
+            }
+
+            if flow.is_break() {
+                break;
+            }
+            row = self.links.down[row];
+        }
+        self.links.uncover(column);
+
+        Ok(flow)
+    }
+}
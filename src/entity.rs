@@ -1,35 +1,576 @@
 use std::{
     cmp::Ordering,
-    collections::{hash_map::Entry, HashMap},
-    io::{stdout, Write},
-    ops::Add,
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    sync::Arc,
 };
 
-// Invariants: always contains a point centered at the origin
-#[derive(Clone)]
+use crate::{
+    bitboard::BitBoard,
+    date::{Date, Month},
+    puzzle::Solution,
+};
+
+// Why a board, tile, or encoding couldn't be used
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PuzzleError {
+    // Boards are encoded with a bit per cell, so they can't have more cells than their encoding
+    // has bits
+    TooManyCells {
+        cells: usize,
+        max: u32,
+    },
+    // Two of the board's AABBs share some cells, given with their indices in the order they were
+    // listed
+    OverlappingAabbs {
+        first: (usize, AABB),
+        second: (usize, AABB),
+    },
+    // A hole, given with its index, doesn't take away any of the board's cells
+    HoleOutsideBoard {
+        index: usize,
+        hole: AABB,
+    },
+    // An AABB's minimum corner lies past its maximum corner
+    InvertedAabb {
+        min: Point,
+        max: Point,
+    },
+    // A tile needs at least one cell
+    EmptyTile,
+    // The points can't all be reached from the origin through edge-adjacent cells, listing those
+    // that can't
+    DisconnectedTile {
+        points: Vec<Point>,
+    },
+    // The tile lists the point more than once
+    DuplicateTilePoint {
+        point: Point,
+    },
+    // A puzzle was built without saying which board it's on
+    MissingBoard,
+    // A cell meant to be left uncovered isn't on the board
+    GoalOutsideBoard {
+        point: Point,
+    },
+    // A label was put on a cell the board doesn't have
+    LabelOutsideBoard {
+        point: Point,
+        label: Label,
+    },
+    // The label is given more than once, or the cell is given more than one label
+    DuplicateLabel {
+        point: Point,
+        label: Label,
+    },
+    // The tiles cover more cells than are left once the goal cells are uncovered
+    TilesTooLarge {
+        area: usize,
+        free: usize,
+    },
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::TooManyCells { cells, max } => {
+                write!(
+                    f,
+                    "the board has {cells} cells, but at most {max} are supported"
+                )
+            }
+            PuzzleError::OverlappingAabbs {
+                first: (first, first_aabb),
+                second: (second, second_aabb),
+            } => write!(
+                f,
+                "the board's rectangle {first} from ({}, {}) to ({}, {}) overlaps rectangle \
+                 {second} from ({}, {}) to ({}, {})",
+                first_aabb.min.x,
+                first_aabb.min.y,
+                first_aabb.max.x,
+                first_aabb.max.y,
+                second_aabb.min.x,
+                second_aabb.min.y,
+                second_aabb.max.x,
+                second_aabb.max.y
+            ),
+            PuzzleError::HoleOutsideBoard { index, hole } => write!(
+                f,
+                "hole {index} from ({}, {}) to ({}, {}) doesn't overlap the board",
+                hole.min.x, hole.min.y, hole.max.x, hole.max.y
+            ),
+            PuzzleError::InvertedAabb { min, max } => write!(
+                f,
+                "the rectangle from ({}, {}) to ({}, {}) has its corners the wrong way around",
+                min.x, min.y, max.x, max.y
+            ),
+            PuzzleError::EmptyTile => write!(f, "a tile must have at least one cell"),
+            PuzzleError::DisconnectedTile { points } => write!(
+                f,
+                "the cells {} aren't connected to the rest of the tile",
+                point_list(points)
+            ),
+            PuzzleError::DuplicateTilePoint { point } => write!(
+                f,
+                "the tile has the cell ({}, {}) more than once",
+                point.x, point.y
+            ),
+            PuzzleError::MissingBoard => write!(f, "the puzzle has no board"),
+            PuzzleError::GoalOutsideBoard { point } => write!(
+                f,
+                "the cell ({}, {}) is meant to be left uncovered, but it isn't on the board",
+                point.x, point.y
+            ),
+            PuzzleError::LabelOutsideBoard { point, label } => write!(
+                f,
+                "{label} is on ({}, {}), which is not on the board",
+                point.x, point.y
+            ),
+            PuzzleError::DuplicateLabel { point, label } => write!(
+                f,
+                "{label} on ({}, {}) clashes with a label given before it",
+                point.x, point.y
+            ),
+            PuzzleError::TilesTooLarge { area, free } => write!(
+                f,
+                "the tiles cover {area} cells, but only {free} are free once the date is left \
+                 uncovered"
+            ),
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+// Why a tile drawing couldn't be read
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TileParseError {
+    // Lines and columns count from 1
+    UnexpectedChar {
+        ch: char,
+        line: usize,
+        column: usize,
+    },
+    NoFilledCells,
+    // As with `PuzzleError::DisconnectedTile`, with the points relative to the tile's origin
+    Disconnected {
+        points: Vec<Point>,
+    },
+}
+
+impl Display for TileParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TileParseError::UnexpectedChar { ch, line, column } => write!(
+                f,
+                "unexpected character `{ch}` on line {line} column {column}, expected `#`, `X`, \
+                 `.`, or a space"
+            ),
+            TileParseError::NoFilledCells => write!(f, "the tile has no filled cells"),
+            TileParseError::Disconnected { points } => write!(
+                f,
+                "the cells {} aren't connected to the rest of the tile",
+                point_list(points)
+            ),
+        }
+    }
+}
+
+impl Error for TileParseError {}
+
+// Why a board drawing couldn't be read
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BoardParseError {
+    // Lines and columns count from 1
+    UnexpectedChar {
+        ch: char,
+        line: usize,
+        column: usize,
+    },
+    NoCells,
+    // The drawing has more cells than the encoding has bits, the first of which too many is on
+    // `line`
+    TooManyCells {
+        max: u32,
+        line: usize,
+    },
+}
+
+impl Display for BoardParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardParseError::UnexpectedChar { ch, line, column } => write!(
+                f,
+                "unexpected character `{ch}` on line {line} column {column}, expected `#`, `.`, \
+                 or a space"
+            ),
+            BoardParseError::NoCells => write!(f, "board has no playable cells"),
+            BoardParseError::TooManyCells { max, line } => write!(
+                f,
+                "board has more than {max} cells, the limit was passed on line {line}"
+            ),
+        }
+    }
+}
+
+impl Error for BoardParseError {}
+
+// Why an encoding couldn't be turned back into cells
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+    // The encoding has these bits set, which stand for no cell of the board
+    NotOnBoard { bits: u128 },
+    // A placement was decoded onto a cell another tile already covers
+    AlreadyCovered { point: Point, tile_id: u8 },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NotOnBoard { bits } => {
+                write!(
+                    f,
+                    "the bits {bits:#x} don't stand for any cell of the board"
+                )
+            }
+            DecodeError::AlreadyCovered { point, tile_id } => write!(
+                f,
+                "the cell ({}, {}) is already covered by tile {tile_id}",
+                point.x, point.y
+            ),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+// Why points couldn't be encoded on a board
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EncodeError {
+    // The first of the points which isn't a cell of the board
+    NotOnBoard { point: Point },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::NotOnBoard { point } => {
+                write!(f, "({}, {}) is not on the board", point.x, point.y)
+            }
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+// Such as `(0, 1), (2, 0)`
+fn point_list(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|point| format!("({}, {})", point.x, point.y))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Which rotations and reflections a tile may be placed in. Ordered from the fewest allowed to the
+// most, so the smaller of two allows only what both do.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transforms {
+    // Only as drawn, for pieces whose artwork has to face a certain way
+    Identity,
+    // Only the four rotations, for pieces which can't be flipped over
+    Rotations,
+    // The four rotations, both as is and flipped over
+    RotationsAndReflections,
+}
+
+impl Transforms {
+    // How many sides of the tile may face up
+    pub fn sides(&self) -> usize {
+        match self {
+            Transforms::RotationsAndReflections => 2,
+            Transforms::Rotations | Transforms::Identity => 1,
+        }
+    }
+
+    // How many ways each side may be turned
+    pub fn rotations(&self) -> usize {
+        match self {
+            Transforms::RotationsAndReflections | Transforms::Rotations => 4,
+            Transforms::Identity => 1,
+        }
+    }
+}
+
+// Invariants: always normalized, so it has a point at the origin with none below it or to its
+// left on the same row, has no point twice, and unless made with `new_disconnected`, every point
+// is connected to the origin through edge-adjacent points
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Tile {
     points: Vec<Point>,
+    // Limits the puzzle's transforms for this tile alone
+    transforms: Transforms,
 }
 
 impl Tile {
-    pub fn new(points: Vec<Point>) -> Self {
-        if !points.iter().any(|point| point.x == 0 && point.y == 0) {
-            panic!("A valid tile must contain a point at the origin.");
+    // The points may lie anywhere, since the tile is normalized
+    pub fn new(points: Vec<Point>) -> Result<Self, PuzzleError> {
+        let tile = Self::new_disconnected(points)?;
+
+        let disconnected = tile.disconnected();
+        if !disconnected.is_empty() {
+            return Err(PuzzleError::DisconnectedTile {
+                points: disconnected,
+            });
         }
 
-        Self { points }
+        Ok(tile)
     }
 
-    pub fn rotate_ccw_90(&mut self) {
+    // Like `new`, but allows pieces in several parts which no physical tile could have
+    pub fn new_disconnected(points: Vec<Point>) -> Result<Self, PuzzleError> {
+        if points.is_empty() {
+            return Err(PuzzleError::EmptyTile);
+        }
+
+        let mut tile = Self {
+            points,
+            transforms: Transforms::RotationsAndReflections,
+        };
+        tile.normalize();
+        if let Some(pair) = tile.points.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(PuzzleError::DuplicateTilePoint { point: pair[0] });
+        }
+
+        Ok(tile)
+    }
+
+    // Reads a tile drawn as an ASCII grid, where `#` or `X` marks a filled cell and `.` or a
+    // space marks an empty one. The first line is the top of the tile.
+    pub fn from_ascii(src: &str) -> Result<Self, TileParseError> {
+        let lines = src.lines().collect::<Vec<_>>();
+        let mut points = Vec::new();
+
+        for (row, line) in lines.iter().enumerate() {
+            let y = (lines.len() - 1 - row) as i32;
+
+            for (x, ch) in line.chars().enumerate() {
+                match ch {
+                    '#' | 'X' => points.push(Point { x: x as i32, y }),
+                    '.' | ' ' => (),
+                    _ => {
+                        return Err(TileParseError::UnexpectedChar {
+                            ch,
+                            line: row + 1,
+                            column: x + 1,
+                        })
+                    }
+                }
+            }
+        }
+
+        if points.is_empty() {
+            return Err(TileParseError::NoFilledCells);
+        }
+
+        let mut tile = Self {
+            points,
+            transforms: Transforms::RotationsAndReflections,
+        };
+        tile.normalize();
+        let disconnected = tile.disconnected();
+        if !disconnected.is_empty() {
+            return Err(TileParseError::Disconnected {
+                points: disconnected,
+            });
+        }
+
+        Ok(tile)
+    }
+
+    // The transforms this tile may be placed with, whatever the puzzle allows. Every transform is
+    // allowed by default.
+    pub fn allowed_transforms(&self) -> Transforms {
+        self.transforms
+    }
+
+    pub fn set_allowed_transforms(&mut self, transforms: Transforms) {
+        self.transforms = transforms;
+    }
+
+    // Every distinct orientation allowed both by `transforms` and by the tile itself. Symmetric
+    // tiles have fewer than the usual eight, or four without reflections.
+    pub fn orientations(&self, transforms: Transforms) -> Vec<Tile> {
+        self.oriented(transforms.min(self.transforms))
+    }
+
+    fn oriented(&self, transforms: Transforms) -> Vec<Tile> {
+        let mut tile = self.clone();
+        let mut orientations = Vec::new();
+
+        for _ in 0..transforms.sides() {
+            for _ in 0..transforms.rotations() {
+                if !orientations.contains(&tile) {
+                    orientations.push(tile.clone());
+                }
+
+                tile.rotate_ccw_90();
+            }
+
+            tile.reflect_over_vert();
+        }
+
+        orientations
+    }
+
+    // The same tile for every rotation, reflection and translation of the shape: the orientation
+    // whose sorted points come first. The shape is all that matters, so the canonical tile allows
+    // every transform.
+    pub fn canonical(&self) -> Tile {
+        let mut canonical = self
+            .oriented(Transforms::RotationsAndReflections)
+            .into_iter()
+            .min_by(|lhs, rhs| lhs.cmp_points(rhs))
+            .expect("every tile has an orientation");
+        canonical.transforms = Transforms::RotationsAndReflections;
+        canonical
+    }
+
+    fn cmp_points(&self, other: &Self) -> Ordering {
+        let key = |tile: &Self| {
+            tile.points
+                .iter()
+                .map(|point| (point.x, point.y))
+                .collect::<Vec<_>>()
+        };
+        key(self).cmp(&key(other))
+    }
+
+    // The points which can't be reached from the origin by stepping between edge-adjacent points,
+    // in order
+    fn disconnected(&self) -> Vec<Point> {
+        let points = self.points.iter().copied().collect::<HashSet<_>>();
+        let mut reached = HashSet::from([Point::ORIGIN]);
+        let mut queue = vec![Point::ORIGIN];
+
+        while let Some(point) = queue.pop() {
+            for step in Point::STEPS {
+                let neighbor = point + step;
+                if points.contains(&neighbor) && reached.insert(neighbor) {
+                    queue.push(neighbor);
+                }
+            }
+        }
+
         self.points
-            .iter_mut()
-            .for_each(|point| *point = point.rotated_ccw_90())
+            .iter()
+            .copied()
+            .filter(|point| !reached.contains(point))
+            .collect()
+    }
+
+    // Translates the tile so that its lowest row lies at y = 0 with the leftmost cell of that row
+    // at the origin, and sorts the points by x and then y. The same shape in the same orientation
+    // always normalizes to the same points, so equal shapes compare equal.
+    //
+    // This anchors the shape on one of its cells rather than moving its bounding box to the
+    // origin, so cells to the left of the anchor have negative x. Placements are found by trying
+    // each cell of the board as the offset, which only reaches every placement if the origin is
+    // a cell of the tile. With the bounding box's corner at the origin instead, a tile whose
+    // corner is empty couldn't be placed where that corner falls in a hole or off the board.
+    pub fn normalize(&mut self) {
+        let anchor = self
+            .points
+            .iter()
+            .min_by_key(|point| (point.y, point.x))
+            .copied()
+            .unwrap_or(Point::ORIGIN);
+
+        for point in &mut self.points {
+            *point -= anchor;
+        }
+        self.points.sort_by_key(|point| (point.x, point.y));
+    }
+
+    // Rotations and reflections renormalize the tile afterwards
+    pub fn rotate_ccw_90(&mut self) {
+        self.transform(Point::rotated_ccw_90);
+    }
+
+    pub fn rotate_cw_90(&mut self) {
+        self.transform(Point::rotated_cw_90);
+    }
+
+    pub fn rotate_180(&mut self) {
+        self.transform(Point::rotated_180);
     }
 
     pub fn reflect_over_vert(&mut self) {
+        self.transform(Point::reflected_over_vert);
+    }
+
+    pub fn reflect_over_horiz(&mut self) {
+        self.transform(Point::reflected_over_horiz);
+    }
+
+    pub fn rotated_ccw_90(&self) -> Self {
+        self.transformed(Point::rotated_ccw_90)
+    }
+
+    pub fn rotated_cw_90(&self) -> Self {
+        self.transformed(Point::rotated_cw_90)
+    }
+
+    pub fn rotated_180(&self) -> Self {
+        self.transformed(Point::rotated_180)
+    }
+
+    pub fn reflected_over_vert(&self) -> Self {
+        self.transformed(Point::reflected_over_vert)
+    }
+
+    pub fn reflected_over_horiz(&self) -> Self {
+        self.transformed(Point::reflected_over_horiz)
+    }
+
+    fn transform(&mut self, transform: fn(&Point) -> Point) {
         self.points
             .iter_mut()
-            .for_each(|point| *point = point.reflected_over_vert())
+            .for_each(|point| *point = transform(point));
+        self.normalize();
+    }
+
+    fn transformed(&self, transform: fn(&Point) -> Point) -> Self {
+        let mut tile = self.clone();
+        tile.transform(transform);
+        tile
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    // How many cells the tile covers
+    pub fn area(&self) -> usize {
+        self.points.len()
+    }
+
+    // The smallest rectangle holding every cell of the tile
+    pub fn bounding_box(&self) -> AABB {
+        let min = Point {
+            x: self.points.iter().map(|point| point.x).min().unwrap_or(0),
+            y: self.points.iter().map(|point| point.y).min().unwrap_or(0),
+        };
+        let max = Point {
+            x: self.points.iter().map(|point| point.x).max().unwrap_or(0),
+            y: self.points.iter().map(|point| point.y).max().unwrap_or(0),
+        };
+        AABB { min, max }
     }
 
     pub fn offset_points(&self, offset: Point) -> impl Iterator<Item = Point> + '_ {
@@ -37,7 +578,39 @@ impl Tile {
     }
 }
 
+// A tile compared as a free polyomino, so that it equals its rotations and reflections. Ordered
+// by the canonical tiles' points, which gives sets of them a stable order.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CanonicalTile(Tile);
+
+impl CanonicalTile {
+    pub fn new(tile: &Tile) -> Self {
+        Self(tile.canonical())
+    }
+
+    pub fn tile(&self) -> &Tile {
+        &self.0
+    }
+
+    pub fn into_tile(self) -> Tile {
+        self.0
+    }
+}
+
+impl PartialOrd for CanonicalTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalTile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_points(&other.0)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     // positive y
     // ^
@@ -49,6 +622,21 @@ pub struct Point {
 }
 
 impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    // One cell right, left, up and down
+    pub const STEPS: [Point; 4] = [
+        Point { x: 1, y: 0 },
+        Point { x: -1, y: 0 },
+        Point { x: 0, y: 1 },
+        Point { x: 0, y: -1 },
+    ];
+
+    // Orders points the way the board is read: the top row first, each from left to right
+    pub fn cmp_reading_order(&self, other: &Self) -> Ordering {
+        other.y.cmp(&self.y).then(self.x.cmp(&other.x))
+    }
+
     pub fn rotated_ccw_90(&self) -> Self {
         Self {
             x: -self.y,
@@ -56,12 +644,30 @@ impl Point {
         }
     }
 
+    pub fn rotated_cw_90(&self) -> Self {
+        Self {
+            x: self.y,
+            y: -self.x,
+        }
+    }
+
+    pub fn rotated_180(&self) -> Self {
+        -*self
+    }
+
     pub fn reflected_over_vert(&self) -> Self {
         Self {
             x: -self.x,
             y: self.y,
         }
     }
+
+    pub fn reflected_over_horiz(&self) -> Self {
+        Self {
+            x: self.x,
+            y: -self.y,
+        }
+    }
 }
 
 impl Add for Point {
@@ -75,8 +681,55 @@ impl Add for Point {
     }
 }
 
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+// Scales both coordinates
+impl Mul<i32> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 // Invariants: min.x <= max.x and min.y <= max.y
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct AABB {
     // Inclusive
     min: Point,
@@ -85,205 +738,1536 @@ pub struct AABB {
 }
 
 impl AABB {
-    pub fn new(min: Point, max: Point) -> Self {
-        assert!(min.x <= max.x && min.y <= max.y);
+    pub fn new(min: Point, max: Point) -> Result<Self, PuzzleError> {
+        if min.x > max.x || min.y > max.y {
+            return Err(PuzzleError::InvertedAabb { min, max });
+        }
 
-        Self { min, max }
+        Ok(Self { min, max })
     }
 
-    pub fn points(&self) -> impl Iterator<Item = Point> {
-        let min = self.min;
-        let max = self.max;
-
-        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| Point { x, y }))
+    pub fn min(&self) -> Point {
+        self.min
     }
-}
 
-// Invariants: has no more than 64 squares, constituent AABBs do not overlap
-#[derive(Clone)]
-pub struct EncodingBoard {
-    aabbs: Vec<AABB>,
-    encoding: HashMap<Point, u64>,
-}
+    pub fn max(&self) -> Point {
+        self.max
+    }
 
-impl EncodingBoard {
-    pub fn new(aabbs: Vec<AABB>) -> Self {
-        let mut encoding = HashMap::new();
-        let mut enc = 1u64;
+    pub fn width(&self) -> usize {
+        self.max.x.abs_diff(self.min.x) as usize + 1
+    }
 
-        for aabb in &aabbs {
-            for point in aabb.points() {
-                if enc == 0 {
-                    panic!("More than 64 tiles in Board");
-                }
+    pub fn height(&self) -> usize {
+        self.max.y.abs_diff(self.min.y) as usize + 1
+    }
 
-                match encoding.entry(point) {
-                    Entry::Vacant(entry) => {
-                        entry.insert(enc);
-                    }
-                    Entry::Occupied(..) => panic!("Overlapping AABBs in Board"),
-                }
+    // How many cells the rectangle holds
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
 
-                enc = enc.overflowing_shl(1).0;
-            }
+    // Moved by `offset`
+    pub fn translate(&self, offset: Point) -> AABB {
+        AABB {
+            min: self.min + offset,
+            max: self.max + offset,
         }
+    }
 
-        Self { aabbs, encoding }
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
     }
 
-    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
-        self.aabbs.iter().flat_map(|aabb| aabb.points())
+    // Whether the rectangles share a cell. Rectangles which only touch along an edge don't.
+    pub fn intersects(&self, other: &AABB) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
     }
 
-    pub fn encode(&self, points: impl Iterator<Item = Point>) -> Option<u64> {
-        points
-            .map(|point| self.encoding.get(&point).copied())
-            .reduce(|a, b| match (a, b) {
-                (Some(a), Some(b)) => Some(a | b),
-                _ => None,
-            })
-            .flatten()
+    // Whether the rectangle lies inside `bounds` once moved by `offset`
+    pub fn fits_within(&self, offset: Point, bounds: &AABB) -> bool {
+        let moved = self.translate(offset);
+        bounds.contains(moved.min) && bounds.contains(moved.max)
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let min = self.min;
+        let max = self.max;
+
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| Point { x, y }))
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum Square {
-    Covered { tile_id: u8 },
-    Uncovered,
-    Unknown,
+// What's printed on a cell of the board
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Label {
+    Month(Month),
+    Day(u8),
+    // Anything else a custom board shows, which no date refers to
+    Custom(String),
 }
 
-pub struct DecodingBoard {
-    decoding: HashMap<u64, (usize, usize)>,
-    rows: Vec<Vec<Square>>,
+impl Display for Label {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::Month(month) => write!(f, "{month}"),
+            Label::Day(day) => write!(f, "day {day}"),
+            Label::Custom(text) => write!(f, "`{text}`"),
+        }
+    }
 }
 
-impl DecodingBoard {
-    pub fn decode(&mut self, enc: u64, tile_id: Option<u8>) {
-        let mut mask = 1u64;
-        while mask != 0 {
-            if enc & mask != 0 {
-                let &(col, row) = self
-                    .decoding
-                    .get(&mask)
-                    .expect("Encoding not compatible with this board");
-                self.rows[col][row] = if let Some(id) = tile_id {
-                    Square::Covered { tile_id: id }
-                } else {
-                    Square::Uncovered
-                };
-            }
+// One of the eight ways of turning a board or flipping it over, about the origin
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BoardTransform {
+    Identity,
+    RotateCcw90,
+    Rotate180,
+    RotateCw90,
+    ReflectOverVert,
+    ReflectOverHoriz,
+    // Over the line y = x
+    ReflectOverDiagonal,
+    // Over the line y = -x
+    ReflectOverAntiDiagonal,
+}
 
-            mask = mask.overflowing_shl(1).0;
+impl BoardTransform {
+    pub const ALL: [Self; 8] = [
+        Self::Identity,
+        Self::RotateCcw90,
+        Self::Rotate180,
+        Self::RotateCw90,
+        Self::ReflectOverVert,
+        Self::ReflectOverHoriz,
+        Self::ReflectOverDiagonal,
+        Self::ReflectOverAntiDiagonal,
+    ];
+
+    pub fn apply(self, point: Point) -> Point {
+        match self {
+            Self::Identity => point,
+            Self::RotateCcw90 => point.rotated_ccw_90(),
+            Self::Rotate180 => point.rotated_180(),
+            Self::RotateCw90 => point.rotated_cw_90(),
+            Self::ReflectOverVert => point.reflected_over_vert(),
+            Self::ReflectOverHoriz => point.reflected_over_horiz(),
+            Self::ReflectOverDiagonal => Point {
+                x: point.y,
+                y: point.x,
+            },
+            Self::ReflectOverAntiDiagonal => Point {
+                x: -point.y,
+                y: -point.x,
+            },
         }
     }
+}
 
-    pub fn print(&self) {
-        let mut stdout = stdout().lock();
-
-        for (col_idx, row) in self.rows.iter().enumerate() {
-            for (row_idx, &square) in row.iter().enumerate() {
-                if col_idx == 0
-                    || row_idx >= self.rows[col_idx - 1].len()
-                    || self.rows[col_idx - 1][row_idx] != square
-                {
-                    stdout.write(b"+---").unwrap();
-                } else {
-                    stdout.write(b"+   ").unwrap();
-                }
-            }
+// Where each cell's bit is looked up, which is the hot path of generating placements. Boards are
+// small and mostly filled in, so a grid over their bounds is usually cheapest, but cells spread
+// far apart would need a huge grid, and then a map is used instead.
+#[derive(Clone)]
+enum Lookup<B> {
+    // Indexed by row from the bottom of the bounds and then column from the left, with no bits
+    // where there's no cell
+    Grid { bounds: AABB, bits: Vec<B> },
+    Sparse(HashMap<Point, B>),
+}
 
-            if col_idx > 0 {
-                for _ in row.len()..self.rows[col_idx - 1].len() {
-                    stdout.write(b"+---").unwrap();
-                }
-            }
+impl<B: BitBoard> Lookup<B> {
+    // Grids are used while they have at most this many squares per cell
+    const MAX_SPARSENESS: usize = 16;
 
-            stdout.write(b"+\n").unwrap();
+    // `cells` are indexed by bit position and in `bounds`
+    fn new(cells: &[Point], bounds: AABB) -> Self {
+        let bits = cells
+            .iter()
+            .enumerate()
+            .map(|(bit, &point)| (point, B::bit(bit as u32)));
 
-            for (row_idx, &square) in row.iter().enumerate() {
-                let left = if row_idx == 0 || row[row_idx - 1] != square {
-                    '|' as u8
-                } else {
-                    32
-                };
+        if bounds.area() > Self::MAX_SPARSENESS * cells.len().max(1) {
+            return Lookup::Sparse(bits.collect());
+        }
 
-                let center = match square {
-                    Square::Covered { .. } => 32,
-                    Square::Uncovered => '#' as u8,
-                    Square::Unknown => '?' as u8,
-                };
+        let mut grid = vec![B::EMPTY; bounds.area()];
+        for (point, enc) in bits {
+            grid[Self::index(&bounds, point)] = enc;
+        }
+        Lookup::Grid { bounds, bits: grid }
+    }
 
-                stdout.write(&[left, 32, center, 32]).unwrap();
-            }
-            stdout.write(b"|\n").unwrap();
+    // The point has to be in the bounds
+    #[inline]
+    fn index(bounds: &AABB, point: Point) -> usize {
+        (point.y - bounds.min.y) as usize * bounds.width() + (point.x - bounds.min.x) as usize
+    }
 
-            if col_idx == self.rows.len() - 1 {
-                for _ in 0..row.len() {
-                    stdout.write(b"+---").unwrap();
+    #[inline]
+    fn get(&self, point: Point) -> Option<B> {
+        match self {
+            Lookup::Grid { bounds, bits } => {
+                if !bounds.contains(point) {
+                    return None;
                 }
-                stdout.write(b"+\n").unwrap();
+                let enc = bits[Self::index(bounds, point)];
+                (!enc.is_empty()).then_some(enc)
             }
+            Lookup::Sparse(bits) => bits.get(&point).copied(),
         }
     }
+
+    fn contains(&self, point: Point) -> bool {
+        self.get(point).is_some()
+    }
 }
 
-impl From<EncodingBoard> for DecodingBoard {
-    fn from(board: EncodingBoard) -> Self {
-        fn rev_i32_order(x: i32) -> i32 {
-            if x == i32::MIN {
-                i32::MAX
-            } else {
-                1 - x
-            }
+// Invariants: has no more squares than `B` has bits, and no square twice. The squares are
+// numbered in reading order however the board was built, so the same squares always get the same
+// bits and encodings mean the same thing wherever they're read.
+#[derive(Clone)]
+pub struct EncodingBoard<B = u64> {
+    encoding: Lookup<B>,
+    bounds: AABB,
+    // The point each bit stands for, indexed by bit position, which puts them in reading order.
+    // Shared with the solutions on the board.
+    cells: Arc<[Point]>,
+    // Every cell's bit
+    full: B,
+    // The cells of each row with any, top row first, and of each column with any, leftmost first
+    rows: Vec<(i32, B)>,
+    cols: Vec<(i32, B)>,
+    // The cells edge-adjacent to each cell, indexed by bit position
+    neighbors: Vec<B>,
+    // The labeled cells in the order they were labeled. No cell and no label appears twice.
+    labels: Vec<(Point, Label)>,
+}
+
+impl<B: BitBoard> EncodingBoard<B> {
+    // Every cell of the AABBs
+    pub fn new(aabbs: Vec<AABB>) -> Result<Self, PuzzleError> {
+        check_overlaps(&aabbs)?;
+        Self::from_cells(aabbs.iter().flat_map(AABB::points).collect())
+    }
+
+    // A board of any shape. Points given more than once are only counted once.
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Result<Self, PuzzleError> {
+        let mut cells = points.into_iter().collect::<Vec<_>>();
+        cells.sort_by(Point::cmp_reading_order);
+        cells.dedup();
+        Self::from_cells(cells)
+    }
+
+    // Reads a board drawn as an ASCII grid, where `#` marks a playable cell and `.` or a space
+    // marks an absent one. The first line of the drawing is the top of the board, the bottom line
+    // sits at y = 0, and the first column at x = 0. Trailing blank lines are ignored.
+    pub fn from_ascii(src: &str) -> Result<Self, BoardParseError> {
+        let mut lines = src.lines().collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
         }
 
-        let mut points = board.points().collect::<Vec<_>>();
-        points.sort_by(|lhs, rhs| {
-            let y_lhs = rev_i32_order(lhs.y);
-            let y_rhs = rev_i32_order(rhs.y);
-
-            let y_cmp = y_lhs.cmp(&y_rhs);
-            if y_cmp == Ordering::Equal {
-                let x_lhs = lhs.x;
-                let x_rhs = rhs.x;
-
-                x_lhs.cmp(&x_rhs)
-            } else {
-                y_cmp
+        let mut points = Vec::new();
+
+        for (line_idx, line) in lines.iter().enumerate() {
+            let y = (lines.len() - 1 - line_idx) as i32;
+
+            for (x, ch) in line.chars().enumerate() {
+                match ch {
+                    '#' => {
+                        if points.len() == B::BITS as usize {
+                            return Err(BoardParseError::TooManyCells {
+                                max: B::BITS,
+                                line: line_idx + 1,
+                            });
+                        }
+
+                        points.push(Point { x: x as i32, y });
+                    }
+                    '.' | ' ' => (),
+                    _ => {
+                        return Err(BoardParseError::UnexpectedChar {
+                            ch,
+                            line: line_idx + 1,
+                            column: x + 1,
+                        })
+                    }
+                }
+            }
+        }
+
+        if points.is_empty() {
+            return Err(BoardParseError::NoCells);
+        }
+
+        Ok(Self::from_points(points).expect("the number of cells was checked"))
+    }
+
+    // Draws the board in the form `from_ascii` reads, with spaces for absent cells. The drawing
+    // starts at the corner of the board's bounds, so boards lying elsewhere than the origin read
+    // back translated.
+    pub fn to_ascii(&self) -> String {
+        let bounds = self.bounds();
+        let mut out = String::new();
+
+        for y in (bounds.min.y..=bounds.max.y).rev() {
+            let row = (bounds.min.x..=bounds.max.x)
+                .map(|x| {
+                    if self.encoding.contains(Point { x, y }) {
+                        '#'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>();
+            out.push_str(row.trim_end());
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // The cells of `adds` which aren't in any of `holes`. Holes may overlap each other, but each
+    // has to take away some cell, since one that doesn't is probably a mistake.
+    pub fn with_holes(adds: Vec<AABB>, holes: Vec<AABB>) -> Result<Self, PuzzleError> {
+        check_overlaps(&adds)?;
+        if let Some((index, &hole)) = holes
+            .iter()
+            .enumerate()
+            .find(|(_, hole)| !adds.iter().any(|add| add.intersects(hole)))
+        {
+            return Err(PuzzleError::HoleOutsideBoard { index, hole });
+        }
+
+        Self::from_cells(
+            adds.iter()
+                .flat_map(AABB::points)
+                .filter(|&point| !holes.iter().any(|hole| hole.contains(point)))
+                .collect(),
+        )
+    }
+
+    // Numbers the cells in reading order. They must not repeat any.
+    fn from_cells(mut cells: Vec<Point>) -> Result<Self, PuzzleError> {
+        cells.sort_by(Point::cmp_reading_order);
+        if cells.len() > B::BITS as usize {
+            return Err(PuzzleError::TooManyCells {
+                cells: cells.len(),
+                max: B::BITS,
+            });
+        }
+
+        debug_assert!(
+            cells.windows(2).all(|pair| pair[0] != pair[1]),
+            "the cells were checked for repeats"
+        );
+        let bounds = AABB {
+            min: Point {
+                x: cells.iter().map(|point| point.x).min().unwrap_or(0),
+                y: cells.iter().map(|point| point.y).min().unwrap_or(0),
+            },
+            max: Point {
+                x: cells.iter().map(|point| point.x).max().unwrap_or(0),
+                y: cells.iter().map(|point| point.y).max().unwrap_or(0),
+            },
+        };
+        let encoding = Lookup::new(&cells, bounds);
+
+        let mut full = B::EMPTY;
+        let mut rows = BTreeMap::new();
+        let mut cols = BTreeMap::new();
+        for (bit, &point) in cells.iter().enumerate() {
+            let enc = B::bit(bit as u32);
+            full |= enc;
+            *rows.entry(point.y).or_insert(B::EMPTY) |= enc;
+            *cols.entry(point.x).or_insert(B::EMPTY) |= enc;
+        }
+
+        let neighbors = cells
+            .iter()
+            .map(|&point| {
+                Point::STEPS
+                    .into_iter()
+                    .filter_map(|step| encoding.get(point + step))
+                    .fold(B::EMPTY, |mask, enc| mask | enc)
+            })
+            .collect();
+
+        Ok(Self {
+            encoding,
+            bounds,
+            cells: cells.into(),
+            full,
+            rows: rows.into_iter().rev().collect(),
+            cols: cols.into_iter().collect(),
+            neighbors,
+            labels: Vec::new(),
+        })
+    }
+
+    // The board moved so that its bounds start at the origin, along with how far it was moved, so
+    // that points meant for it such as goal cells can be moved the same way. Labels move with
+    // their cells, and since moving keeps the reading order, so do the bits.
+    pub fn normalized(&self) -> (Self, Point) {
+        let offset = Point::ORIGIN - self.bounds().min;
+        let mut board = Self::from_cells(self.cells.iter().map(|&point| point + offset).collect())
+            .expect("moving the cells doesn't change how many there are");
+        board.labels = self
+            .labels
+            .iter()
+            .map(|(point, label)| (*point + offset, label.clone()))
+            .collect();
+        (board, offset)
+    }
+
+    // The smallest rectangle holding every cell, which is just the origin for a board with none
+    pub fn bounds(&self) -> AABB {
+        self.bounds
+    }
+
+    // In bit order
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.cells.iter().copied()
+    }
+
+    // The groups of cells connected through edge-adjacent cells, each in bit order and ordered by
+    // their first cell. Cells touching only at a corner aren't connected, since no tile could
+    // cross between them. A board in more than one part is most likely a mistake.
+    pub fn connected_components(&self) -> Vec<Vec<Point>> {
+        let mut component_of = HashMap::with_capacity(self.cells.len());
+        let mut count = 0;
+
+        for &start in self.cells.iter() {
+            if component_of.contains_key(&start) {
+                continue;
+            }
+
+            let component = count;
+            count += 1;
+            component_of.insert(start, component);
+            let mut queue = vec![start];
+            while let Some(point) = queue.pop() {
+                for step in Point::STEPS {
+                    let neighbor = point + step;
+                    if self.encoding.contains(neighbor) && !component_of.contains_key(&neighbor) {
+                        component_of.insert(neighbor, component);
+                        queue.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        let mut components = vec![Vec::new(); count];
+        for &point in self.cells.iter() {
+            components[component_of[&point]].push(point);
+        }
+        components
+    }
+
+    // Leaves out which point wasn't on the board, so that generating placements, where most
+    // offsets are expected to fall off the board, stays fast
+    pub fn encode(&self, points: impl Iterator<Item = Point>) -> Option<B> {
+        points
+            .map(|point| self.encoding.get(point))
+            .reduce(|a, b| match (a, b) {
+                (Some(a), Some(b)) => Some(a | b),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    // The encoding of every cell, which a solution's tiles and goal cells add up to
+    pub fn full_mask(&self) -> B {
+        self.full
+    }
+
+    // The cells of each row which has any, as `(y, mask)`, top row first
+    pub fn row_masks(&self) -> &[(i32, B)] {
+        &self.rows
+    }
+
+    // The cells of each column which has any, as `(x, mask)`, leftmost column first
+    pub fn col_masks(&self) -> &[(i32, B)] {
+        &self.cols
+    }
+
+    pub fn row_mask(&self, y: i32) -> B {
+        self.rows
+            .iter()
+            .find(|&&(row, _)| row == y)
+            .map_or(B::EMPTY, |&(_, mask)| mask)
+    }
+
+    pub fn col_mask(&self, x: i32) -> B {
+        self.cols
+            .iter()
+            .find(|&&(col, _)| col == x)
+            .map_or(B::EMPTY, |&(_, mask)| mask)
+    }
+
+    // The cells which share an edge with the cell at `bit`, which has to be one of the board's.
+    // Cells on the edge of the board or next to a hole have fewer than four.
+    pub fn neighbors_of_bit(&self, bit: u8) -> B {
+        self.neighbors[bit as usize]
+    }
+
+    // The cells which share an edge with the point, if it's on the board
+    pub fn neighbors_of_point(&self, point: Point) -> Option<B> {
+        self.bit_index(point).map(|bit| self.neighbors_of_bit(bit))
+    }
+
+    // How many of the board's cells aren't in `occupied`
+    pub fn free_cells(&self, occupied: B) -> u32 {
+        (self.full & !occupied).count_ones()
+    }
+
+    // The board's cells which aren't in `occupied`, in bit order
+    pub fn free_points(&self, occupied: B) -> impl Iterator<Item = Point> + '_ {
+        (self.full & !occupied)
+            .ones()
+            .map(|bit| self.cells[bit as usize])
+    }
+
+    // Like `encode`, but for points which are expected to be on the board, such as ones from the
+    // user. No points encode to no cells.
+    pub fn encode_checked(
+        &self,
+        points: impl IntoIterator<Item = Point>,
+    ) -> Result<B, EncodeError> {
+        points.into_iter().try_fold(B::EMPTY, |enc, point| {
+            self.encoding
+                .get(point)
+                .map(|bit| enc | bit)
+                .ok_or(EncodeError::NotOnBoard { point })
+        })
+    }
+
+    // The inverse of `encode`, in bit order
+    pub fn decode_points(&self, enc: B) -> Result<Vec<Point>, DecodeError> {
+        let outside = enc
+            .ones()
+            .skip_while(|&bit| (bit as usize) < self.cells.len());
+        let bits = outside.fold(0, |bits, bit| bits | 1 << bit);
+        if bits != 0 {
+            return Err(DecodeError::NotOnBoard { bits });
+        }
+
+        Ok(enc.ones().map(|bit| self.cells[bit as usize]).collect())
+    }
+
+    // Labels the cell, replacing any label it had. A label can only be on one cell, so it's taken
+    // off any other cell that had it.
+    pub fn set_label(&mut self, point: Point, label: Label) -> Result<(), PuzzleError> {
+        if !self.encoding.contains(point) {
+            return Err(PuzzleError::LabelOutsideBoard { point, label });
+        }
+
+        self.labels
+            .retain(|(other_point, other)| *other_point != point && *other != label);
+        self.labels.push((point, label));
+        Ok(())
+    }
+
+    pub fn point_of(&self, label: &Label) -> Option<Point> {
+        self.labels
+            .iter()
+            .find(|(_, other)| other == label)
+            .map(|&(point, _)| point)
+    }
+
+    pub fn label_at(&self, point: Point) -> Option<&Label> {
+        self.labels
+            .iter()
+            .find(|&&(other, _)| other == point)
+            .map(|(_, label)| label)
+    }
+
+    // In the order the cells were labeled
+    pub fn labels(&self) -> impl Iterator<Item = (Point, &Label)> + '_ {
+        self.labels.iter().map(|(point, label)| (*point, label))
+    }
+
+    // The month's cell followed by the day's cell, which are left uncovered for the date. Labels
+    // which are missing are left out, which `check_date_labels` rules out before anything is
+    // solved.
+    pub fn date_points(&self, date: Date) -> Vec<Point> {
+        let day = u8::try_from(date.day).ok().map(Label::Day);
+        [Some(Label::Month(date.month)), day]
+            .into_iter()
+            .flatten()
+            .filter_map(|label| self.point_of(&label))
+            .collect()
+    }
+
+    // The date shown by leaving exactly the given cells uncovered
+    pub fn date_at(&self, points: &[Point]) -> Result<Date, String> {
+        let mut month = None;
+        let mut day = None;
+
+        for &point in points {
+            match self.label_at(point) {
+                Some(&Label::Month(found)) if month.is_none() => month = Some(found),
+                Some(&Label::Day(found)) if day.is_none() => day = Some(found),
+                Some(Label::Month(..)) => {
+                    return Err("the cells marked `#` show more than one month".to_owned())
+                }
+                Some(Label::Day(..)) => {
+                    return Err("the cells marked `#` show more than one day".to_owned())
+                }
+                Some(Label::Custom(..)) | None => {
+                    return Err(format!(
+                        "({}, {}) is marked `#`, but it isn't a month or a day",
+                        point.x, point.y
+                    ))
+                }
             }
-        });
-
-        let mut prev_y = None;
-        let mut rows = Vec::new();
-        let mut row = Vec::new();
-        let mut point_map = HashMap::new();
-
-        for point in points {
-            match (point.y, prev_y) {
-                (y, Some(py)) if y != py => {
-                    rows.push(row);
-                    row = Vec::new();
-                    prev_y = Some(y);
+        }
+
+        match (month, day) {
+            (Some(month), Some(day)) => Ok(Date {
+                year: None,
+                month,
+                day: day.into(),
+            }),
+            _ => Err("the cells marked `#` should show one month and one day".to_owned()),
+        }
+    }
+
+    // Checks that every month and every day from 1 to 31 labels a cell, and that no other days
+    // do, returning a description of every problem found otherwise
+    pub fn check_date_labels(&self) -> Result<(), Vec<String>> {
+        let mut problems = Month::ALL
+            .into_iter()
+            .map(Label::Month)
+            .chain((1..=31).map(Label::Day))
+            .filter(|label| self.point_of(label).is_none())
+            .map(|label| format!("no cell is labeled {label}"))
+            .collect::<Vec<_>>();
+
+        for (_, label) in &self.labels {
+            if let Label::Day(day) = label {
+                if !(1..=31).contains(day) {
+                    problems.push(format!("{label} is not a day of the month"));
                 }
-                (y, None) => {
-                    prev_y = Some(y);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    // The transforms which map the board's cells onto themselves once they're moved back within
+    // the board's bounds, starting with the identity. Labeled cells have to stay where they are,
+    // or the transformed board would read differently.
+    pub fn symmetries(&self) -> Vec<BoardTransform> {
+        BoardTransform::ALL
+            .into_iter()
+            .filter(|&transform| {
+                let aligned = self.aligned(transform);
+                self.cells
+                    .iter()
+                    .all(|&point| self.encoding.contains(aligned(point)))
+                    && self
+                        .labels
+                        .iter()
+                        .all(|&(point, _)| aligned(point) == point)
+            })
+            .collect()
+    }
+
+    // Where the encoded cells end up once the board is transformed and moved back within its
+    // bounds, as with `symmetries`. Only transforms which are symmetries of the board are
+    // guaranteed to keep every cell on it.
+    pub fn transform_encoding(&self, transform: BoardTransform, enc: B) -> Option<B> {
+        let aligned = self.aligned(transform);
+        let points = self.decode_points(enc).ok()?;
+        if points.is_empty() {
+            return Some(B::EMPTY);
+        }
+        self.encode(points.into_iter().map(aligned))
+    }
+
+    fn aligned(&self, transform: BoardTransform) -> impl Fn(Point) -> Point {
+        let bounds = self.bounds();
+        let corners = [bounds.min, bounds.max].map(|corner| transform.apply(corner));
+        let min = Point {
+            x: corners[0].x.min(corners[1].x),
+            y: corners[0].y.min(corners[1].y),
+        };
+        let offset = bounds.min - min;
+        move |point| transform.apply(point) + offset
+    }
+
+    // Each cell with its bit, in bit order
+    pub fn cells(&self) -> impl Iterator<Item = (u8, Point)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(bit, &point)| (bit as u8, point))
+    }
+
+    pub fn bit_index(&self, point: Point) -> Option<u8> {
+        self.encoding
+            .get(point)
+            .map(|enc| enc.trailing_zeros() as u8)
+    }
+
+    pub(crate) fn shared_cells(&self) -> &Arc<[Point]> {
+        &self.cells
+    }
+
+    // Whether the boards have the same cells, however they were built and whichever order their
+    // cells are numbered in. This is what `==` compares, so labels don't matter.
+    pub fn cells_eq<C: BitBoard>(&self, other: &EncodingBoard<C>) -> bool {
+        self.cells.len() == other.cells.len()
+            && self
+                .cells
+                .iter()
+                .all(|point| other.encoding.contains(*point))
+    }
+}
+
+impl<B: BitBoard> PartialEq for EncodingBoard<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells_eq(other)
+    }
+}
+
+impl<B: BitBoard> Eq for EncodingBoard<B> {}
+
+// Consistent with `cells_eq`, so the cells are hashed in an order that doesn't depend on the bits
+impl<B: BitBoard> Hash for EncodingBoard<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut cells = self.cells.to_vec();
+        cells.sort_by_key(|point| (point.x, point.y));
+        cells.hash(state);
+    }
+}
+
+// Reports the first two AABBs found to overlap
+fn check_overlaps(aabbs: &[AABB]) -> Result<(), PuzzleError> {
+    for (first, first_aabb) in aabbs.iter().enumerate() {
+        if let Some((second, second_aabb)) = aabbs
+            .iter()
+            .enumerate()
+            .skip(first + 1)
+            .find(|(_, other)| first_aabb.intersects(other))
+        {
+            return Err(PuzzleError::OverlappingAabbs {
+                first: (first, *first_aabb),
+                second: (second, *second_aabb),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Square {
+    Covered { tile_id: u8 },
+    Uncovered,
+    // Not covered by any tile yet, while a search is still running
+    Empty,
+    Unknown,
+}
+
+pub struct DecodingBoard {
+    // The square each bit of an encoding stands for, indexed by bit position
+    decoding: Vec<(usize, usize)>,
+    rows: Vec<Vec<Square>>,
+    points: Vec<Vec<Point>>,
+    labels: Vec<Vec<Option<Label>>>,
+}
+
+impl DecodingBoard {
+    // Marks the squares in the encoding as covered by the tile, or as left uncovered without one.
+    // Unless `overwrite` is set, squares some tile already covers are never covered again, since
+    // that means two placements overlap, so nothing is changed then.
+    pub fn decode<B: BitBoard>(
+        &mut self,
+        enc: B,
+        tile_id: Option<u8>,
+        overwrite: bool,
+    ) -> Result<(), DecodeError> {
+        let square = if let Some(id) = tile_id {
+            Square::Covered { tile_id: id }
+        } else {
+            Square::Uncovered
+        };
+
+        let squares = self.squares_of(enc)?;
+        if !overwrite {
+            for &(row, col) in &squares {
+                if let Square::Covered { tile_id } = self.rows[row][col] {
+                    return Err(DecodeError::AlreadyCovered {
+                        point: self.points[row][col],
+                        tile_id,
+                    });
                 }
-                _ => (),
             }
+        }
+        self.set(&squares, square);
+        Ok(())
+    }
 
-            point_map.insert(point, (rows.len(), row.len()));
-            row.push(Square::Unknown);
+    // Sets every square in the encoding to `square`, whatever was there before. Nothing is
+    // changed if the encoding doesn't fit the board.
+    pub fn fill<B: BitBoard>(&mut self, enc: B, square: Square) -> Result<(), DecodeError> {
+        let squares = self.squares_of(enc)?;
+        self.set(&squares, square);
+        Ok(())
+    }
+
+    // The row and column of each square in the encoding
+    fn squares_of<B: BitBoard>(&self, enc: B) -> Result<Vec<(usize, usize)>, DecodeError> {
+        let outside = enc
+            .ones()
+            .skip_while(|&bit| (bit as usize) < self.decoding.len());
+        let bits = outside.fold(0, |bits, bit| bits | 1 << bit);
+        if bits != 0 {
+            return Err(DecodeError::NotOnBoard { bits });
+        }
+
+        Ok(enc.ones().map(|bit| self.decoding[bit as usize]).collect())
+    }
+
+    fn set(&mut self, squares: &[(usize, usize)], square: Square) {
+        for &(row, col) in squares {
+            self.rows[row][col] = square;
+        }
+    }
+
+    // Shows the solution's tiles, with the cells no tile covers left uncovered. Every square of
+    // the board is overwritten, so the same board can draw solution after solution.
+    pub fn draw<B: BitBoard>(&mut self, solution: &Solution<B>) -> Result<(), DecodeError> {
+        self.clear();
+        for (id, &enc) in solution.tiles().iter().enumerate() {
+            self.decode(enc, Some(id as u8), false)?;
+        }
+        self.decode(solution.goal(), None, false)
+    }
+
+    // Sets every square back to unknown, as it was when the board was made
+    pub fn clear(&mut self) {
+        for row in &mut self.rows {
+            row.fill(Square::Unknown);
+        }
+    }
+
+    pub fn rows(&self) -> &[Vec<Square>] {
+        &self.rows
+    }
+
+    // Every square with its point on the board, row by row as in `rows`
+    pub fn squares(&self) -> impl Iterator<Item = (Point, Square)> + '_ {
+        let points = self.points.iter().flatten().copied();
+        points.zip(self.rows.iter().flatten().copied())
+    }
+
+    // Aligned with `rows`, giving the board coordinates of each square
+    pub fn points(&self) -> &[Vec<Point>] {
+        &self.points
+    }
+
+    // Aligned with `rows`, giving the label printed on each square, if it has one
+    pub fn labels(&self) -> &[Vec<Option<Label>>] {
+        &self.labels
+    }
+}
+
+impl<B: BitBoard> From<EncodingBoard<B>> for DecodingBoard {
+    fn from(board: EncodingBoard<B>) -> Self {
+        Self::from(&board)
+    }
+}
+
+impl<B: BitBoard> From<&EncodingBoard<B>> for DecodingBoard {
+    fn from(board: &EncodingBoard<B>) -> Self {
+        // The cells are numbered in reading order, so each row's bits run from left to right
+        let mut decoding = vec![(0, 0); board.cells.len()];
+        let mut rows = Vec::with_capacity(board.rows.len());
+        let mut points = Vec::with_capacity(board.rows.len());
+        let mut labels = Vec::with_capacity(board.rows.len());
+
+        for (row_idx, &(_, mask)) in board.rows.iter().enumerate() {
+            let row = mask
+                .ones()
+                .enumerate()
+                .map(|(col_idx, bit)| {
+                    decoding[bit as usize] = (row_idx, col_idx);
+                    board.cells[bit as usize]
+                })
+                .collect::<Vec<_>>();
+            rows.push(vec![Square::Unknown; row.len()]);
+            labels.push(
+                row.iter()
+                    .map(|&point| board.label_at(point).cloned())
+                    .collect(),
+            );
+            points.push(row);
         }
-        rows.push(row);
 
         Self {
-            decoding: board
-                .encoding
-                .into_iter()
-                .map(|(point, enc)| (enc, *point_map.get(&point).unwrap()))
-                .collect(),
+            decoding,
             rows,
+            points,
+            labels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{puzzle::Puzzle, variant};
+
+    fn classic() -> Puzzle {
+        Puzzle::from_variant(variant::classic()).unwrap()
+    }
+
+    fn square_at(board: &DecodingBoard, point: Point) -> Option<Square> {
+        board
+            .squares()
+            .find(|&(other, _)| other == point)
+            .map(|(_, square)| square)
+    }
+
+    fn tile(points: &[(i32, i32)]) -> Tile {
+        Tile::new(points.iter().map(|&(x, y)| Point { x, y }).collect()).unwrap()
+    }
+
+    // The top line is the highest row, and the shape is moved so its lowest left cell is at the
+    // origin
+    #[test]
+    fn tile_from_ascii_is_y_up() {
+        assert_eq!(
+            Tile::from_ascii("##\n #\n #"),
+            Ok(tile(&[(0, 2), (1, 2), (1, 1), (1, 0)]))
+        );
+        assert_eq!(
+            Tile::from_ascii("...\n.X.\n.XX\n"),
+            Ok(tile(&[(0, 1), (0, 0), (1, 0)]))
+        );
+    }
+
+    #[test]
+    fn tile_from_ascii_rejects_bad_drawings() {
+        assert_eq!(
+            Tile::from_ascii(" . \n\n"),
+            Err(TileParseError::NoFilledCells)
+        );
+        assert_eq!(
+            Tile::from_ascii("##\n#o"),
+            Err(TileParseError::UnexpectedChar {
+                ch: 'o',
+                line: 2,
+                column: 2
+            })
+        );
+        assert!(matches!(
+            Tile::from_ascii("#.\n.#"),
+            Err(TileParseError::Disconnected { .. })
+        ));
+    }
+
+    fn points(points: &[(i32, i32)]) -> Vec<Point> {
+        points.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    // Cells touching only at a corner aren't connected, so the cells cut off from the origin are
+    // the ones named
+    #[test]
+    fn diagonal_tiles_are_disconnected() {
+        assert_eq!(
+            Tile::new(points(&[(0, 0), (1, 1), (2, 2)])),
+            Err(PuzzleError::DisconnectedTile {
+                points: points(&[(1, 1), (2, 2)])
+            })
+        );
+        assert_eq!(
+            Tile::new(points(&[(0, 0), (1, 0), (2, 1), (3, 1)])),
+            Err(PuzzleError::DisconnectedTile {
+                points: points(&[(2, 1), (3, 1)])
+            })
+        );
+    }
+
+    #[test]
+    fn disconnected_tiles_can_be_made_on_purpose() {
+        let tile = Tile::new_disconnected(points(&[(0, 0), (1, 1)])).unwrap();
+        assert_eq!(tile.points(), points(&[(0, 0), (1, 1)]));
+        assert!(Tile::new(points(&[(0, 0), (0, 1), (1, 1)])).is_ok());
+    }
+
+    #[test]
+    fn tiles_need_distinct_points() {
+        assert_eq!(Tile::new(Vec::new()), Err(PuzzleError::EmptyTile));
+        assert_eq!(
+            Tile::new(points(&[(0, 0), (0, 1), (0, 0)])),
+            Err(PuzzleError::DuplicateTilePoint {
+                point: Point::ORIGIN
+            })
+        );
+    }
+
+    fn grid() -> impl Iterator<Item = Point> {
+        (-4..=4).flat_map(|x| (-4..=4).map(move |y| Point { x, y }))
+    }
+
+    #[test]
+    fn point_arithmetic_identities() {
+        let q = Point { x: 3, y: -2 };
+        for p in grid() {
+            assert_eq!(p - p, Point::ORIGIN);
+            assert_eq!(p + Point::ORIGIN, p);
+            assert_eq!(p + q - q, p);
+            assert_eq!(p + q, q + p);
+            assert_eq!(-(-p), p);
+            assert_eq!(p + -p, Point::ORIGIN);
+            assert_eq!(p * -1, -p);
+            assert_eq!(p * 3, p + p + p);
+
+            let mut r = p;
+            r += q;
+            assert_eq!(r, p + q);
+            r -= q;
+            assert_eq!(r, p);
+        }
+    }
+
+    #[test]
+    fn reading_order_is_top_to_bottom_then_left_to_right() {
+        let mut points = vec![
+            Point { x: 1, y: 0 },
+            Point { x: 0, y: 0 },
+            Point { x: 5, y: 1 },
+            Point { x: -3, y: -1 },
+            Point { x: 0, y: 1 },
+        ];
+        points.sort_by(Point::cmp_reading_order);
+        assert_eq!(
+            points,
+            [
+                Point { x: 0, y: 1 },
+                Point { x: 5, y: 1 },
+                Point { x: 0, y: 0 },
+                Point { x: 1, y: 0 },
+                Point { x: -3, y: -1 },
+            ]
+        );
+    }
+
+    // Every pair of extreme points compares consistently, without overflowing
+    #[test]
+    fn reading_order_at_extreme_coordinates() {
+        let coords = [i32::MIN, i32::MIN + 1, -1, 0, 1, i32::MAX - 1, i32::MAX];
+        let points = coords
+            .iter()
+            .flat_map(|&x| coords.iter().map(move |&y| Point { x, y }))
+            .collect::<Vec<_>>();
+
+        for a in &points {
+            assert_eq!(a.cmp_reading_order(a), Ordering::Equal);
+            for b in &points {
+                assert_eq!(a.cmp_reading_order(b), b.cmp_reading_order(a).reverse());
+                let expected = b.y.cmp(&a.y).then(a.x.cmp(&b.x));
+                assert_eq!(a.cmp_reading_order(b), expected);
+            }
+        }
+
+        let top = Point { x: 0, y: i32::MAX };
+        let bottom = Point { x: 0, y: i32::MIN };
+        assert_eq!(top.cmp_reading_order(&bottom), Ordering::Less);
+        let left = Point { x: i32::MIN, y: 0 };
+        let right = Point { x: i32::MAX, y: 0 };
+        assert_eq!(left.cmp_reading_order(&right), Ordering::Less);
+    }
+
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> AABB {
+        AABB::new(Point { x: x0, y: y0 }, Point { x: x1, y: y1 }).unwrap()
+    }
+
+    #[test]
+    fn aabb_contains_its_edges() {
+        let aabb = rect(0, 0, 2, 1);
+        assert!(aabb.contains(Point::ORIGIN));
+        assert!(aabb.contains(Point { x: 2, y: 1 }));
+        assert!(!aabb.contains(Point { x: 3, y: 1 }));
+        assert!(!aabb.contains(Point { x: 0, y: -1 }));
+        assert_eq!(
+            aabb.points().filter(|&point| aabb.contains(point)).count(),
+            6
+        );
+    }
+
+    #[test]
+    fn aabbs_intersect_when_they_share_a_cell() {
+        let aabb = rect(0, 0, 2, 2);
+        assert!(aabb.intersects(&aabb));
+        assert!(aabb.intersects(&rect(2, 2, 4, 4)));
+        assert!(aabb.intersects(&rect(1, 1, 1, 1)));
+        assert!(aabb.intersects(&rect(-1, 1, 5, 1)));
+        // Next to each other along an edge or at a corner
+        assert!(!aabb.intersects(&rect(3, 0, 4, 2)));
+        assert!(!aabb.intersects(&rect(0, 3, 2, 3)));
+        assert!(!aabb.intersects(&rect(3, 3, 3, 3)));
+        assert!(!aabb.intersects(&rect(-2, -2, -1, -1)));
+    }
+
+    #[test]
+    fn touching_aabbs_make_a_board() {
+        let board =
+            EncodingBoard::<u64>::new(vec![rect(0, 0, 2, 0), rect(0, 1, 6, 4), rect(3, 0, 3, 0)])
+                .unwrap();
+        assert_eq!(board.points().count(), 3 + 28 + 1);
+    }
+
+    #[test]
+    fn overlapping_aabbs_are_named() {
+        let aabbs = vec![rect(0, 0, 2, 0), rect(0, 1, 6, 4), rect(6, 4, 7, 5)];
+        assert_eq!(
+            EncodingBoard::<u64>::new(aabbs).err(),
+            Some(PuzzleError::OverlappingAabbs {
+                first: (1, rect(0, 1, 6, 4)),
+                second: (2, rect(6, 4, 7, 5)),
+            })
+        );
+        assert_eq!(
+            AABB::new(Point { x: 1, y: 0 }, Point::ORIGIN),
+            Err(PuzzleError::InvertedAabb {
+                min: Point { x: 1, y: 0 },
+                max: Point::ORIGIN
+            })
+        );
+    }
+
+    // The classic board is a 7 by 7 square without the notch at the end of the month strip and the
+    // end of the last row
+    #[test]
+    fn classic_board_from_one_rectangle_and_two_holes() {
+        let board = EncodingBoard::<u64>::with_holes(
+            vec![rect(0, 0, 6, 6)],
+            vec![rect(6, 5, 6, 6), rect(3, 0, 6, 0)],
+        )
+        .unwrap();
+        assert!(board.cells_eq(classic().board()));
+    }
+
+    #[test]
+    fn holes_off_the_board_are_flagged() {
+        let holes = vec![rect(1, 1, 1, 1), rect(5, 5, 6, 6)];
+        assert_eq!(
+            EncodingBoard::<u64>::with_holes(vec![rect(0, 0, 3, 3)], holes).err(),
+            Some(PuzzleError::HoleOutsideBoard {
+                index: 1,
+                hole: rect(5, 5, 6, 6)
+            })
+        );
+
+        // A hole only has to cut into the board somewhere
+        let board =
+            EncodingBoard::<u64>::with_holes(vec![rect(0, 0, 3, 3)], vec![rect(3, 3, 9, 9)])
+                .unwrap();
+        assert_eq!(board.points().count(), 15);
+    }
+
+    #[test]
+    fn classic_board_matches_its_drawing() {
+        let drawing = "\
+######
+######
+#######
+#######
+#######
+#######
+###
+";
+        let parsed = EncodingBoard::<u64>::from_ascii(drawing).unwrap();
+        assert!(parsed == *classic().board());
+        assert_eq!(classic().board().to_ascii(), drawing);
+    }
+
+    // Boards made of cells picked from a 6 by 6 square, read back from their drawings
+    #[test]
+    fn board_drawings_round_trip() {
+        for seed in 1..200u64 {
+            let mask = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 28;
+            let cells = (0..36)
+                .filter(|bit| mask & 1 << bit != 0)
+                .map(|bit| Point {
+                    x: bit % 6 + 3,
+                    y: bit / 6 - 2,
+                })
+                .collect::<Vec<_>>();
+            let Ok(board) = EncodingBoard::<u64>::from_points(cells) else {
+                continue;
+            };
+
+            let drawing = board.to_ascii();
+            let parsed = EncodingBoard::<u64>::from_ascii(&drawing).unwrap();
+            assert!(parsed == board.normalized().0, "{drawing}");
+            assert_eq!(parsed.to_ascii(), drawing);
+        }
+    }
+
+    #[test]
+    fn board_drawing_errors_point_at_the_problem() {
+        assert_eq!(
+            EncodingBoard::<u64>::from_ascii("###\n#.#\n##x\n").err(),
+            Some(BoardParseError::UnexpectedChar {
+                ch: 'x',
+                line: 3,
+                column: 3
+            })
+        );
+        assert_eq!(
+            EncodingBoard::<u64>::from_ascii(" ..\n\n").err(),
+            Some(BoardParseError::NoCells)
+        );
+        let too_many = "########\n".repeat(9);
+        assert_eq!(
+            EncodingBoard::<u64>::from_ascii(&too_many).err(),
+            Some(BoardParseError::TooManyCells { max: 64, line: 9 })
+        );
+    }
+
+    fn drawn(drawing: &str) -> EncodingBoard {
+        EncodingBoard::from_ascii(drawing).unwrap()
+    }
+
+    #[test]
+    fn classic_board_is_in_one_piece() {
+        let components = classic().board().connected_components();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), 43);
+    }
+
+    #[test]
+    fn diagonal_cells_are_separate_components() {
+        let components = drawn("#.\n.#\n").connected_components();
+        assert_eq!(
+            components,
+            [vec![Point { x: 0, y: 1 }], vec![Point { x: 1, y: 0 }]]
+        );
+    }
+
+    #[test]
+    fn components_are_ordered_by_their_first_cell() {
+        let components = drawn("##.#\n##..\n...#\n").connected_components();
+        let sizes = components.iter().map(Vec::len).collect::<Vec<_>>();
+        assert_eq!(sizes, [4, 1, 1]);
+        assert_eq!(components[1], [Point { x: 3, y: 2 }]);
+        assert_eq!(components[2], [Point { x: 3, y: 0 }]);
+    }
+
+    #[test]
+    fn decode_points_undoes_encode() {
+        let board = classic().board().clone();
+        let all = board.points().collect::<Vec<_>>();
+        for start in 0..all.len() {
+            // Every third cell from `start` on, in a mixed up order
+            let mut points = all[start..].iter().copied().step_by(3).collect::<Vec<_>>();
+            points.reverse();
+            let enc = board.encode(points.iter().copied()).unwrap();
+
+            let mut sorted = points.clone();
+            sorted.sort_by(Point::cmp_reading_order);
+            assert_eq!(board.decode_points(enc), Ok(sorted));
+        }
+        assert_eq!(board.decode_points(0), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn decode_points_rejects_bits_off_the_board() {
+        let board = classic().board().clone();
+        let off = 1 << 43 | 1 << 60;
+        assert_eq!(
+            board.decode_points(off | 1),
+            Err(DecodeError::NotOnBoard { bits: off.into() })
+        );
+    }
+
+    fn hash_of(board: &EncodingBoard) -> u64 {
+        use std::hash::DefaultHasher;
+
+        let mut hasher = DefaultHasher::new();
+        board.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // The classic board cut into rectangles another way
+    #[test]
+    fn boards_compare_by_their_cells() {
+        let rows =
+            EncodingBoard::new(vec![rect(0, 5, 5, 6), rect(0, 1, 6, 4), rect(0, 0, 2, 0)]).unwrap();
+        let columns =
+            EncodingBoard::new(vec![rect(0, 0, 2, 6), rect(3, 1, 5, 6), rect(6, 1, 6, 4)]).unwrap();
+
+        assert!(rows == columns);
+        assert!(rows.cells_eq(&columns));
+        assert_eq!(hash_of(&rows), hash_of(&columns));
+        assert!(columns == *classic().board());
+    }
+
+    #[test]
+    fn shifted_boards_differ() {
+        let board = classic().board().clone();
+        let shifted =
+            EncodingBoard::from_points(board.points().map(|point| point + Point { x: 1, y: 0 }))
+                .unwrap();
+        assert!(board != shifted);
+        assert!(!board.cells_eq(&shifted));
+        assert!(board == shifted.normalized().0);
+    }
+
+    // Bits are given out in reading order: the two month rows of six, the four day rows of seven,
+    // and the last three days
+    #[test]
+    fn classic_board_bit_assignment() {
+        let board = classic().board().clone();
+        let rows = [(6, 6), (5, 6), (4, 7), (3, 7), (2, 7), (1, 7), (0, 3)];
+        let expected = rows
+            .into_iter()
+            .flat_map(|(y, width)| (0..width).map(move |x| Point { x, y }))
+            .enumerate()
+            .map(|(bit, point)| (bit as u8, point))
+            .collect::<Vec<_>>();
+        assert_eq!(board.cells().collect::<Vec<_>>(), expected);
+
+        for (bit, point) in expected {
+            assert_eq!(board.bit_index(point), Some(bit));
+        }
+        assert_eq!(board.bit_index(Point { x: 6, y: 6 }), None);
+        assert_eq!(board.bit_index(Point { x: -1, y: 0 }), None);
+    }
+
+    // However the cells are listed, they get the same bits
+    #[test]
+    fn bit_assignment_ignores_how_the_board_was_built() {
+        let board = classic().board().clone();
+        let mut points = board.points().collect::<Vec<_>>();
+        points.reverse();
+        points.rotate_left(17);
+        let copy = EncodingBoard::<u64>::from_points(points).unwrap();
+        assert!(copy.cells().eq(board.cells()));
+
+        let wide = EncodingBoard::<u128>::from_points(board.points()).unwrap();
+        assert!(wide.cells().eq(board.cells()));
+    }
+
+    fn neighbors(board: &EncodingBoard, x: i32, y: i32) -> Option<u64> {
+        board.neighbors_of_point(Point { x, y })
+    }
+
+    fn encode(board: &EncodingBoard, points: &[(i32, i32)]) -> Option<u64> {
+        board.encode(points.iter().map(|&(x, y)| Point { x, y }))
+    }
+
+    // The notches at the top right and bottom right of the classic board leave the cells around
+    // them with fewer neighbors
+    #[test]
+    fn neighbors_at_the_classic_notches() {
+        let board = classic().board().clone();
+        let cases: [(_, &[_]); 8] = [
+            ((0, 6), &[(1, 6), (0, 5)]),
+            ((5, 6), &[(4, 6), (5, 5)]),
+            ((5, 5), &[(4, 5), (5, 6), (5, 4)]),
+            ((6, 4), &[(5, 4), (6, 3)]),
+            ((6, 1), &[(5, 1), (6, 2)]),
+            ((3, 1), &[(2, 1), (4, 1), (3, 2)]),
+            ((2, 0), &[(1, 0), (2, 1)]),
+            ((3, 3), &[(2, 3), (4, 3), (3, 2), (3, 4)]),
+        ];
+        for ((x, y), expected) in cases {
+            assert_eq!(
+                neighbors(&board, x, y),
+                encode(&board, expected),
+                "({x}, {y})"
+            );
+        }
+        assert_eq!(neighbors(&board, 6, 6), None);
+        assert_eq!(neighbors(&board, 6, 5), None);
+        assert_eq!(neighbors(&board, 3, 0), None);
+    }
+
+    #[test]
+    fn neighbors_are_the_adjacent_cells() {
+        let board = classic().board().clone();
+        for (bit, point) in board.cells() {
+            let expected = board
+                .points()
+                .filter(|other| (point.x - other.x).abs() + (point.y - other.y).abs() == 1)
+                .fold(0, |mask, other| mask | 1 << board.bit_index(other).unwrap());
+            let mask = board.neighbors_of_bit(bit);
+            assert_eq!(mask, expected, "({}, {})", point.x, point.y);
+            assert_eq!(board.neighbors_of_point(point), Some(mask));
+            for other in mask.ones() {
+                assert!(board.neighbors_of_bit(other as u8).contains(bit as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn point_transforms_follow_the_group_laws() {
+        for point in grid() {
+            assert_eq!(point.rotated_cw_90().rotated_ccw_90(), point);
+            assert_eq!(point.rotated_ccw_90().rotated_cw_90(), point);
+            assert_eq!(point.rotated_180().rotated_180(), point);
+            assert_eq!(point.reflected_over_horiz().reflected_over_horiz(), point);
+            assert_eq!(point.reflected_over_vert().reflected_over_vert(), point);
+
+            let ccw = |point: Point| point.rotated_ccw_90();
+            assert_eq!(ccw(ccw(point)), point.rotated_180());
+            assert_eq!(ccw(ccw(ccw(point))), point.rotated_cw_90());
+            assert_eq!(ccw(ccw(ccw(ccw(point)))), point);
+            assert_eq!(
+                point.reflected_over_vert().rotated_180(),
+                point.reflected_over_horiz()
+            );
+        }
+    }
+
+    #[test]
+    fn tile_transforms_follow_the_group_laws() {
+        for tile in (variant::classic().tiles)() {
+            assert_eq!(tile.rotated_cw_90().rotated_ccw_90(), tile);
+            assert_eq!(tile.rotated_180().rotated_180(), tile);
+            assert_eq!(tile.reflected_over_horiz().reflected_over_horiz(), tile);
+            assert_eq!(tile.reflected_over_vert().reflected_over_vert(), tile);
+            assert_eq!(tile.rotated_ccw_90().rotated_ccw_90(), tile.rotated_180());
+            assert_eq!(tile.rotated_180().rotated_ccw_90(), tile.rotated_cw_90());
+            assert_eq!(
+                tile.reflected_over_vert().rotated_180(),
+                tile.reflected_over_horiz()
+            );
+
+            // The methods which change the tile agree with the ones which return a new one
+            let mut changed = tile.clone();
+            changed.rotate_cw_90();
+            assert_eq!(changed, tile.rotated_cw_90());
+            changed.rotate_180();
+            assert_eq!(changed, tile.rotated_ccw_90());
+            changed.reflect_over_horiz();
+            assert_eq!(changed, tile.rotated_ccw_90().reflected_over_horiz());
+        }
+    }
+
+    #[test]
+    fn classic_board_labels_every_date() {
+        assert_eq!(classic().board().check_date_labels(), Ok(()));
+    }
+
+    #[test]
+    fn missing_and_extra_labels_are_listed() {
+        let mut board = (variant::classic().board)();
+        let day_31 = board.point_of(&Label::Day(31)).unwrap();
+        let march = board.point_of(&Label::Month(Month::March)).unwrap();
+        board
+            .set_label(day_31, Label::Custom("X".to_owned()))
+            .unwrap();
+        board.set_label(march, Label::Day(32)).unwrap();
+
+        assert_eq!(
+            board.check_date_labels(),
+            Err(vec![
+                "no cell is labeled March".to_owned(),
+                "no cell is labeled day 31".to_owned(),
+                "day 32 is not a day of the month".to_owned(),
+            ])
+        );
+    }
+
+    // Each label is on one cell and each cell has one label, so months and days can't share cells
+    #[test]
+    fn labels_are_on_one_cell_of_the_board() {
+        let mut board = (variant::classic().board)();
+        let day_1 = board.point_of(&Label::Day(1)).unwrap();
+        let day_2 = board.point_of(&Label::Day(2)).unwrap();
+
+        board.set_label(day_2, Label::Day(1)).unwrap();
+        assert_eq!(board.point_of(&Label::Day(1)), Some(day_2));
+        assert_eq!(board.label_at(day_1), None);
+        assert_eq!(board.point_of(&Label::Day(2)), None);
+
+        board.set_label(day_2, Label::Month(Month::May)).unwrap();
+        assert_eq!(board.point_of(&Label::Day(1)), None);
+
+        let off = Point { x: 100, y: 100 };
+        assert_eq!(
+            board.set_label(off, Label::Day(1)),
+            Err(PuzzleError::LabelOutsideBoard {
+                point: off,
+                label: Label::Day(1)
+            })
+        );
+    }
+
+    #[test]
+    fn decode_rejects_bits_off_the_board() {
+        let puzzle = classic();
+        let mut board = DecodingBoard::from(puzzle.board());
+        let off = 1u64 << 63;
+        let error = DecodeError::NotOnBoard {
+            bits: u128::from(off),
+        };
+
+        assert_eq!(board.decode(off | 1, Some(0), false), Err(error.clone()));
+        assert_eq!(board.fill(off, Square::Empty), Err(error));
+        assert!(board.squares().all(|(_, square)| square == Square::Unknown));
+    }
+
+    #[test]
+    fn decode_rejects_overlaps_unless_overwriting() {
+        let puzzle = classic();
+        let mut board = DecodingBoard::from(puzzle.board());
+        let point = puzzle.board().points().next().unwrap();
+        let enc = puzzle.board().encode([point].into_iter()).unwrap();
+
+        board.decode(enc, Some(2), false).unwrap();
+        assert_eq!(
+            board.decode(enc, Some(3), false),
+            Err(DecodeError::AlreadyCovered { point, tile_id: 2 })
+        );
+        assert_eq!(
+            square_at(&board, point),
+            Some(Square::Covered { tile_id: 2 })
+        );
+
+        board.decode(enc, Some(3), true).unwrap();
+        assert_eq!(
+            square_at(&board, point),
+            Some(Square::Covered { tile_id: 3 })
+        );
+    }
+
+    // Solutions from the solver never overlap or leave the board, so drawing them never fails
+    #[test]
+    fn solver_solutions_always_draw() {
+        let puzzle = classic();
+        let solver = puzzle.solver();
+        let mut board = DecodingBoard::from(puzzle.board());
+        let dates = [
+            (Month::January, 1),
+            (Month::February, 29),
+            (Month::October, 13),
+        ];
+        for (month, day) in dates {
+            let date = Date {
+                year: None,
+                month,
+                day,
+            };
+            let goal = puzzle.board().date_points(date);
+            let solution = solver.solve(&goal).unwrap().unwrap();
+            assert_eq!(board.draw(&solution), Ok(()), "{date}");
         }
     }
 }
@@ -0,0 +1,128 @@
+use std::io;
+
+use crate::{
+    backtrack::Interrupted,
+    entity::PuzzleError,
+    render::{json_string, RenderError},
+};
+
+pub enum Error {
+    // Bad arguments, dates, or definition files
+    InvalidInput(String),
+    // The search finished without finding a solution
+    NoSolution(String),
+    // A user-supplied solution failed verification
+    Rejected(String),
+    // The search ran out of time, so whether a solution exists is unknown
+    TimedOut(String),
+    // Something went wrong which no input should be able to cause, meaning there's a bug
+    Internal(String),
+    // Stdout was closed before everything was written, as by piping into `head`. Whatever closed
+    // it has all the output it wanted, so this isn't reported.
+    OutputClosed,
+}
+
+// Boards and tiles come from the variant or the user's files, so problems with them are the input's
+// fault
+impl From<PuzzleError> for Error {
+    fn from(error: PuzzleError) -> Self {
+        Error::InvalidInput(error.to_string())
+    }
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NoSolution(..) => 1,
+            Error::InvalidInput(..) => 2,
+            Error::TimedOut(..) => 3,
+            Error::Rejected(..) => 4,
+            Error::Internal(..) => 5,
+            Error::OutputClosed => 0,
+        }
+    }
+
+    // A stable identifier for scripts consuming `--json-errors`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::NoSolution(..) => "no_solution",
+            Error::InvalidInput(..) => "invalid_input",
+            Error::TimedOut(..) => "timeout",
+            Error::Rejected(..) => "invalid_solution",
+            Error::Internal(..) => "internal",
+            Error::OutputClosed => "output_closed",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::InvalidInput(message)
+            | Error::NoSolution(message)
+            | Error::TimedOut(message)
+            | Error::Rejected(message)
+            | Error::Internal(message) => message,
+            Error::OutputClosed => "the output was closed",
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"kind\":{},\"message\":{}}}",
+            json_string(self.kind()),
+            json_string(self.message())
+        )
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        // Solutions come from the search, so one which can't be drawn is a bug rather than a
+        // problem with the output
+        let drawing = error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<RenderError>());
+        if let Some(error) = drawing {
+            Error::Internal(format!("failed to draw a solution: {error}"))
+        } else if error.kind() == io::ErrorKind::BrokenPipe {
+            Error::OutputClosed
+        } else {
+            Error::InvalidInput(format!("failed to write the output: {error}"))
+        }
+    }
+}
+
+impl From<Interrupted> for Error {
+    fn from(interrupted: Interrupted) -> Self {
+        Error::TimedOut(match interrupted {
+            Interrupted::TimedOut => {
+                "Search timed out, so it's unknown whether a solution exists".to_owned()
+            }
+            Interrupted::Cancelled => {
+                "Search was cancelled, so it's unknown whether a solution exists".to_owned()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::DecodeError;
+
+    #[test]
+    fn drawing_failures_are_internal() {
+        let decode = DecodeError::NotOnBoard { bits: 1 << 63 };
+        let error = Error::from(io::Error::other(RenderError::Decode(decode)));
+        assert!(matches!(error, Error::Internal(..)));
+        assert_eq!(error.exit_code(), 5);
+        assert_eq!(error.kind(), "internal");
+    }
+
+    #[test]
+    fn other_write_failures_are_not() {
+        let closed = Error::from(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(matches!(closed, Error::OutputClosed));
+        let full = Error::from(io::Error::other("disk full"));
+        assert!(matches!(full, Error::InvalidInput(..)));
+    }
+}
@@ -0,0 +1,219 @@
+use std::{collections::BTreeSet, fs, ops::ControlFlow, path::Path};
+
+use crate::{
+    backtrack::{self, Limits, NodeBudget},
+    date::Date,
+    entity::{CanonicalTile, Point, Tile, Transforms},
+    error::Error,
+    puzzle::filter_placements,
+    render, BitBoard, Context, Puzzle,
+};
+
+// The sizes of the polyominoes tried when no pool is given
+pub const DEFAULT_SIZES: [usize; 2] = [5, 6];
+
+pub struct Options<'a> {
+    // The candidate tiles, or every free polyomino of the default sizes
+    pub pool: Option<Vec<Tile>>,
+    // Placement attempts allowed per date before giving up on a set
+    pub budget: u64,
+    // How many winning sets to find before stopping
+    pub limit: usize,
+    // A directory to write each winning set to, instead of printing them
+    pub output: Option<&'a Path>,
+}
+
+// Searches subsets of the pool whose area leaves exactly the date cells uncovered for ones that
+// solve every date. A set is rejected as soon as any date fails, and the date that rejected the
+// last set is tried first since it's likely to reject the next one too.
+pub fn run<B: BitBoard>(ctx: &Context<B>, options: Options) -> Result<(), Error> {
+    let pool = match options.pool {
+        Some(pool) => pool,
+        None => DEFAULT_SIZES
+            .into_iter()
+            .flat_map(free_polyominoes)
+            .collect(),
+    };
+    // Each subset is checked separately, so the pool as a whole doesn't need to fit
+    let pool = Puzzle::builder()
+        .board(ctx.encoding_board.clone())
+        .tiles(pool)
+        .allow_reflections(ctx.transforms == Transforms::RotationsAndReflections)
+        .build()?;
+    let configurations = pool.configurations();
+    let pool = pool.tiles();
+
+    let mut dates = ctx.dates().collect::<Vec<_>>();
+    let Some(&first) = dates.first() else {
+        return Ok(());
+    };
+    let target = ctx
+        .encoding_board
+        .points()
+        .count()
+        .saturating_sub(ctx.encoding_board.date_points(first).len());
+    eprintln!(
+        "Searching sets of {} candidate tiles covering {target} cells",
+        pool.len()
+    );
+
+    let mut found = 0;
+    let mut tested = 0u64;
+    let mut result = Ok(());
+
+    // Whether every subset was visited or the search stopped early, the outcome is in `result`
+    let _ = subsets(pool, target, &mut Vec::new(), 0, &mut |set| {
+        tested += 1;
+        let set_configurations = set
+            .iter()
+            .map(|&index| configurations[index].clone())
+            .collect::<Vec<_>>();
+
+        match first_failure(ctx, &set_configurations, &dates, options.budget) {
+            Ok(Some(failed)) => {
+                dates[..=failed].rotate_right(1);
+                return ControlFlow::Continue(());
+            }
+            Ok(None) => (),
+            Err(error) => {
+                result = Err(error);
+                return ControlFlow::Break(());
+            }
+        }
+
+        found += 1;
+        let tiles = set.iter().map(|&index| &pool[index]).collect::<Vec<_>>();
+        if let Err(error) = report(&tiles, found, options.output) {
+            result = Err(error);
+            return ControlFlow::Break(());
+        }
+
+        if found >= options.limit {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    result?;
+
+    eprintln!("Tested {tested} sets and found {found} that solve every date");
+    if found == 0 {
+        return Err(Error::NoSolution(
+            "No set of tiles solves every date".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+// Calls `visit` with the indices of every subset of `pool` whose total area is `target`, in
+// lexicographic order, stopping early if it breaks
+fn subsets(
+    pool: &[Tile],
+    target: usize,
+    chosen: &mut Vec<usize>,
+    start: usize,
+    visit: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    if target == 0 {
+        return visit(chosen);
+    }
+
+    for index in start..pool.len() {
+        let area = pool[index].area();
+        if area > target {
+            continue;
+        }
+
+        chosen.push(index);
+        let flow = subsets(pool, target - area, chosen, index + 1, visit);
+        chosen.pop();
+        flow?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+// The index of the first date the set can't solve within the budget, if any
+fn first_failure<B: BitBoard>(
+    ctx: &Context<B>,
+    configurations: &[BTreeSet<B>],
+    dates: &[Date],
+    budget: u64,
+) -> Result<Option<usize>, Error> {
+    for (index, &date) in dates.iter().enumerate() {
+        let initial_board = ctx.encode_date(date)?;
+        let (placements, placement_indices) = filter_placements(configurations, initial_board);
+
+        // Running out of budget counts as a failure, so that pathological sets don't stall the
+        // search
+        let solution = backtrack::backtrack(
+            initial_board,
+            &placements,
+            &placement_indices,
+            ctx.order,
+            &Limits::default(),
+            &mut NodeBudget { remaining: budget },
+        );
+        if !matches!(solution, Ok(Some(_))) {
+            return Ok(Some(index));
+        }
+    }
+
+    Ok(None)
+}
+
+// Prints the winning set in the format `--tiles` reads, or writes it to its own file. On stdout
+// each set starts with a `// Set N` comment, so the sets can be told apart.
+fn report(tiles: &[&Tile], number: usize, output: Option<&Path>) -> Result<(), Error> {
+    let drawing = tiles
+        .iter()
+        .map(|tile| render::tile(tile))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let Some(dir) = output else {
+        if number > 1 {
+            println!();
+        }
+        println!("// Set {number}");
+        print!("{drawing}");
+        return Ok(());
+    };
+
+    let path = dir.join(format!("set-{number}.txt"));
+    let failed =
+        |error| Error::InvalidInput(format!("failed to write `{}`: {error}", path.display()));
+    fs::create_dir_all(dir).map_err(failed)?;
+    fs::write(&path, drawing).map_err(failed)?;
+    println!("Wrote {}", path.display());
+
+    Ok(())
+}
+
+// Every polyomino of the given size, counting rotations and reflections of a shape as the same
+pub fn free_polyominoes(size: usize) -> Vec<Tile> {
+    let monomino = Tile::new(vec![Point::ORIGIN]).expect("a single cell is a tile");
+    let mut shapes = BTreeSet::from([CanonicalTile::new(&monomino)]);
+
+    for _ in 1..size {
+        let mut grown = BTreeSet::new();
+        for shape in &shapes {
+            let points = shape.tile().points();
+            for &point in points {
+                for step in Point::STEPS {
+                    let neighbor = point + step;
+                    if !points.contains(&neighbor) {
+                        let mut larger = points.to_vec();
+                        larger.push(neighbor);
+                        let larger = Tile::new(larger).expect("adding a neighbor stays connected");
+                        grown.insert(CanonicalTile::new(&larger));
+                    }
+                }
+            }
+        }
+        shapes = grown;
+    }
+
+    shapes.into_iter().map(CanonicalTile::into_tile).collect()
+}
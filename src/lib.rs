@@ -0,0 +1,27 @@
+// Solves the daily calendar puzzle, where tiles have to cover every cell of a board except the
+// ones showing a date. `Puzzle` describes the board and tiles, and its `Solver` finds solutions.
+
+pub mod backtrack;
+pub mod bitboard;
+mod cell;
+pub mod date;
+#[cfg(feature = "dlx")]
+mod dlx;
+pub mod entity;
+mod mrv;
+pub mod parse;
+#[cfg(feature = "png")]
+mod png;
+pub mod puzzle;
+pub mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod variant;
+pub mod verify;
+
+pub use bitboard::BitBoard;
+pub use entity::{
+    BoardParseError, BoardTransform, DecodeError, EncodeError, EncodingBoard, Label, Point,
+    PuzzleError, Tile, TileParseError, AABB,
+};
+pub use puzzle::{Puzzle, Solution, SolutionIter, SolveError, Solver, TileId};
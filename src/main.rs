@@ -1,169 +1,1710 @@
-use std::collections::HashSet;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{self, Write},
+    ops::ControlFlow,
+    path::Path,
+    process::exit,
+    time::{Duration, Instant},
+};
 
-use crate::entity::{DecodingBoard, Point};
+use date_puzzle::{
+    backtrack, date, entity, parse,
+    puzzle::{self, filter_placements},
+    render, variant, verify, BitBoard, Puzzle, Solution,
+};
 
-mod backtrack;
-mod entity;
+use crate::{
+    animate::Animation,
+    backtrack::{Interrupted, Limits, Order, SearchStats},
+    cli::{Args, Command, Parsed},
+    date::{Date, Month},
+    entity::{DecodingBoard, EncodingBoard, Point, PuzzleError, Tile, Transforms},
+    error::Error,
+    output::Output,
+    progress::Progress,
+    random::Rng,
+    render::Format,
+    variant::Variant,
+    verify::Piece,
+};
 
-macro_rules! aabb {
-    (($x0:literal, $y0:literal) -> ($x1:literal, $y1:literal)) => {
-        $crate::entity::AABB::new(
-            $crate::entity::Point { x: $x0, y: $y0 },
-            $crate::entity::Point { x: $x1, y: $y1 },
-        )
+mod animate;
+mod calendar;
+mod cli;
+mod error;
+mod gen;
+mod output;
+mod parallel;
+mod progress;
+mod random;
+mod repl;
+mod serve;
+
+fn main() {
+    // Checked up front so that errors in the rest of the arguments are reported in the right form
+    let json_errors = std::env::args().any(|arg| arg == "--json-errors");
+
+    let result = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Parsed::Run(args)) => run(*args),
+        Ok(Parsed::Help) => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Err(error) if json_errors => Err(Error::InvalidInput(error)),
+        Err(error) => Err(Error::InvalidInput(format!(
+            "{error}\n\nRun with --help for usage."
+        ))),
     };
+
+    if let Err(error) = result {
+        if let Error::OutputClosed = error {
+            return;
+        }
+        if json_errors {
+            eprintln!("{}", error.to_json());
+        } else {
+            print_error(&error);
+        }
+        exit(error.exit_code());
+    }
 }
 
-macro_rules! enc_board {
-    ($( $aabb:expr, )+) => {
-        $crate::entity::EncodingBoard::new(vec![$( $aabb ),+])
-    };
+fn print_error(error: &Error) {
+    match error {
+        Error::InvalidInput(message) => eprintln!("error: {message}"),
+        Error::Internal(message) => eprintln!("internal error: {message}"),
+        Error::NoSolution(message) | Error::TimedOut(message) | Error::Rejected(message) => {
+            eprintln!("{message}")
+        }
+        Error::OutputClosed => (),
+    }
 }
 
-macro_rules! tile {
-    ($( ($x:literal, $y:literal), )+) => {
-        $crate::entity::Tile::new(vec![$( $crate::entity::Point { x: $x, y: $y } ),+])
+fn run(args: Args) -> Result<(), Error> {
+    let variant = args.variant.unwrap_or_else(variant::classic);
+    let Some(path) = &args.board else {
+        return run_with(&args, variant, (variant.board)());
     };
+
+    // Boards that fit in a `u64` are searched with one, which is faster. Only larger boards need
+    // the wider encoding.
+    let mut board = load(path, parse::parse_board::<u128>)?;
+    // A board without labels of its own shows the dates where the variant's board does
+    if board.labels().next().is_none() {
+        copy_labels(&(variant.board)(), &mut board);
+    }
+    let components = board.connected_components();
+    if components.len() > 1 {
+        let sizes = components
+            .iter()
+            .map(|component| component.len().to_string())
+            .collect::<Vec<_>>();
+        eprintln!(
+            "Warning: the board is split into {} separate parts, of {} cells",
+            components.len(),
+            sizes.join(", ")
+        );
+    }
+    match EncodingBoard::<u64>::from_points(board.points()) {
+        Ok(mut small) => {
+            copy_labels(&board, &mut small);
+            run_with(&args, variant, small)
+        }
+        Err(PuzzleError::TooManyCells { .. }) => run_with(&args, variant, board),
+        Err(error) => Err(error.into()),
+    }
 }
 
-#[repr(i32)]
-#[derive(Clone, Copy)]
-#[allow(dead_code)]
-enum Month {
-    January,
-    February,
-    March,
-    April,
-    May,
-    June,
-    July,
-    August,
-    September,
-    October,
-    November,
-    December,
+// Labels the cells of `to` which are labeled on `from`. Labels on cells `to` doesn't have are left
+// out, and reported as missing once the labels are checked.
+fn copy_labels<B: BitBoard, C: BitBoard>(from: &EncodingBoard<B>, to: &mut EncodingBoard<C>) {
+    for (point, label) in from.labels() {
+        let _ = to.set_label(point, label.clone());
+    }
 }
 
-fn main() {
-    let encoding_board = enc_board!(
-        aabb!((0,0) -> (2,0)),
-        aabb!((0,1) -> (6,4)),
-        aabb!((0,5) -> (5,6)),
-    );
+fn run_with<B: BitBoard>(
+    args: &Args,
+    variant: &Variant,
+    encoding_board: EncodingBoard<B>,
+) -> Result<(), Error> {
+    let mut tiles = match &args.tiles {
+        Some(path) => load(path, parse::parse_tiles)?,
+        None => (variant.tiles)(),
+    };
+    if tiles.is_empty() && !args.exclude_tiles.is_empty() {
+        return Err(Error::InvalidInput(
+            "there are no tiles to exclude".to_owned(),
+        ));
+    }
+    if let Some(&index) = args
+        .exclude_tiles
+        .iter()
+        .find(|&&index| index >= tiles.len())
+    {
+        return Err(Error::InvalidInput(format!(
+            "can't exclude tile {index}, expected a tile from 0 to {}",
+            tiles.len() - 1
+        )));
+    }
+    // The tiles that remain are numbered in their original order
+    tiles = tiles
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !args.exclude_tiles.contains(index))
+        .map(|(_, tile)| tile)
+        .collect();
 
-    #[rustfmt::skip]
-    let tiles = vec![
-        tile! {
-            (0,2),
-            (0,1),
-            (0,0),(1,0),(2,0),
-        },
-        tile! {
-            (0,3),
-            (0,2),
-            (0,1),
-            (0,0),(1,0),
-        },
-        tile! {
-                  (1,3),
-            (0,2),(1,2),
-            (0,1),
-            (0,0),
-        },
-        tile! {
-            (0,2),(1,2),
-            (0,1),(1,1),
-            (0,0),(1,0),
-        },
-        tile! {
-            (0,2),
-            (0,1),(1,1),
-            (0,0),(1,0),
-        },
-        tile! {
-            (0,2),(1,2),
-            (0,1),
-            (0,0),(1,0),
-        },
-        tile! {
-            (0,3),
-            (0,2),
-            (0,1),(1,1),
-            (0,0),
-        },
-        tile! {
-                        (2,2),
-            (0,1),(1,1),(2,1),
-            (0,0),
+    if let Err(problems) = encoding_board.check_date_labels() {
+        let mut message = "the board's date labels are incomplete:".to_owned();
+        for problem in problems {
+            message.push_str("\n  ");
+            message.push_str(&problem);
         }
-    ];
+        return Err(Error::InvalidInput(message));
+    }
 
-    let configurations = tiles
-        .into_iter()
-        .map(|mut tile| {
-            let mut tile_configs = HashSet::new();
-
-            for _ in 0..2 {
-                for _ in 0..4 {
-                    for offset in encoding_board.points() {
-                        if let Some(enc) = encoding_board.encode(tile.offset_points(offset)) {
-                            tile_configs.insert(enc);
-                        }
+    if args.one_sided {
+        eprintln!("Tiles can't be flipped over (--one-sided)");
+    }
+    // Every date leaves the same number of cells uncovered, so any of them will do for checking
+    // that the tiles fit
+    let goal_cells = Date::board_dates()
+        .next()
+        .map(|date| encoding_board.date_points(date))
+        .unwrap_or_default();
+    let puzzle = Puzzle::builder()
+        .board(encoding_board)
+        .tiles(tiles)
+        .allow_reflections(!args.one_sided)
+        .goal_cells(&goal_cells)
+        .build()?;
+
+    let mut ctx = Context {
+        encoding_board: puzzle.board().clone(),
+        tiles: puzzle.tiles().to_vec(),
+        configurations: puzzle.configurations().to_vec(),
+        limits: Limits::default(),
+        threads: parallel::default_threads(),
+        force: false,
+        transforms: puzzle.transforms(),
+        order: Order::Static,
+        quiet: false,
+    };
+
+    // The board and tiles are only set up once, and each line of the REPL is dispatched against
+    // them
+    if args.command == Command::Repl {
+        return repl::run(ctx);
+    }
+
+    ctx.configure(args);
+    dispatch(&ctx, args)
+}
+
+// Runs any command other than `repl`, once the board and tile configurations are built
+fn dispatch<B: BitBoard>(ctx: &Context<B>, args: &Args) -> Result<(), Error> {
+    let tiles = &ctx.tiles;
+    if args.command == Command::Verify {
+        let path = args
+            .solution
+            .as_deref()
+            .expect("verify always has a solution path");
+        let date = resolve_date(args);
+        check_date(date, args.force)?;
+        return verify_solution(ctx, tiles, path, date);
+    }
+
+    if args.command == Command::SolvePartial {
+        return solve_partial(ctx, tiles, args);
+    }
+
+    if args.command == Command::Gen {
+        let pool = match &args.pool {
+            Some(path) => Some(load(path, parse::parse_tiles)?),
+            None => None,
+        };
+        return gen::run(
+            ctx,
+            gen::Options {
+                pool,
+                budget: args.budget.unwrap_or(1_000_000_000),
+                limit: args.limit.unwrap_or(1),
+                output: args.output.as_deref().map(Path::new),
+            },
+        );
+    }
+
+    if args.command == Command::Calendar {
+        return calendar::run(
+            ctx,
+            args.year
+                .or(Date::today().year)
+                .expect("today's year is known"),
+            args.format,
+            args.csv,
+            args.output.as_deref().map(Path::new),
+        );
+    }
+
+    if args.command == Command::Serve {
+        return serve::run(ctx, args.port.unwrap_or(8080), args.timeout);
+    }
+
+    if args.command == Command::ListTiles {
+        list_tiles(tiles, ctx.transforms);
+        return Ok(());
+    }
+
+    if args.command == Command::Bench {
+        return bench(ctx, args.iterations.unwrap_or(1), args.csv);
+    }
+
+    if args.command == Command::Hint {
+        return hint(ctx, tiles, args);
+    }
+
+    if args.command == Command::ByCell {
+        let cell = args.cell.expect("by-cell always has a cell");
+        return by_cell(ctx, args, cell);
+    }
+
+    if args.command == Command::Year {
+        return check_year(ctx);
+    }
+
+    if args.command == Command::Heatmap {
+        return heatmap(ctx, args.month);
+    }
+
+    if args.command == Command::Hardest {
+        return hardest(ctx);
+    }
+
+    if args.command == Command::Unique {
+        return unique(ctx);
+    }
+
+    let mut rng = args.shuffle.then(|| {
+        let seed = args.seed.unwrap_or_else(Rng::entropy_seed);
+        eprintln!("Shuffling placements with seed {seed}");
+        Rng::new(seed)
+    });
+
+    if args.stdin {
+        return solve_stdin(ctx, args, rng.as_mut());
+    }
+
+    if let Some((from, to)) = args.range {
+        let output = args.output.clone().map(Output::new);
+        check_date(from, ctx.force)?;
+        check_date(to, ctx.force)?;
+        let dates = Date::range(from, to, args.wrap).ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "{from} comes after {to}, use `--wrap` to carry on into the next year"
+            ))
+        })?;
+        return solve_range(
+            ctx,
+            dates,
+            args.format,
+            args.columns,
+            output.as_ref(),
+            rng.as_mut(),
+        );
+    }
+
+    let mut date = resolve_date(args);
+    // Counts are reported with the year, so a date given without one is checked as this year's
+    if args.command == Command::Count && date.year.is_none() {
+        date.year = Date::today().year;
+    }
+    check_date(date, args.force)?;
+    let job = ctx.job(date, rng.as_mut())?;
+
+    if args.animate {
+        return animate(ctx, args, &job);
+    }
+
+    let mut stats = SearchStats::default();
+    let started = Instant::now();
+    let outcome = search_date(ctx, args, &job, &mut stats);
+    let elapsed = started.elapsed();
+
+    if args.stats {
+        print_stats(&job, ctx.order, &stats, elapsed);
+    }
+
+    report_date(ctx, args, &job, outcome?)
+}
+
+// The result of searching a single date, depending on which command was run
+enum Outcome {
+    Count {
+        count: u64,
+        // Only counted with --count-distinct
+        distinct: Option<usize>,
+    },
+    All {
+        solutions: Vec<Vec<usize>>,
+        stopped_early: bool,
+    },
+    First(Option<Vec<usize>>),
+}
+
+fn search_date<B: BitBoard>(
+    ctx: &Context<B>,
+    args: &Args,
+    job: &Job<B>,
+    stats: &mut SearchStats,
+) -> Result<Outcome, Error> {
+    let Job {
+        initial_board,
+        placements,
+        placement_indices,
+        ..
+    } = job;
+
+    if args.command == Command::Count && args.count_distinct {
+        let mut solutions = Vec::new();
+        backtrack::search(
+            *initial_board,
+            placements,
+            placement_indices,
+            ctx.order,
+            &ctx.limits,
+            stats,
+            |indices| {
+                solutions.push(ctx.solution(job, indices));
+                ControlFlow::Continue(())
+            },
+        )?;
+        return Ok(Outcome::Count {
+            count: solutions.len() as u64,
+            distinct: Some(puzzle::distinct_solutions(&ctx.encoding_board, solutions).len()),
+        });
+    }
+
+    if args.command == Command::Count {
+        let count = backtrack::count(
+            *initial_board,
+            placements,
+            placement_indices,
+            ctx.order,
+            &ctx.limits,
+            stats,
+        )?;
+        return Ok(Outcome::Count {
+            count,
+            distinct: None,
+        });
+    }
+
+    if args.all {
+        let mut solutions = Vec::new();
+        let mut stopped_early = false;
+        backtrack::search(
+            *initial_board,
+            placements,
+            placement_indices,
+            ctx.order,
+            &ctx.limits,
+            stats,
+            |indices| {
+                solutions.push(indices.to_vec());
+                if args.limit.is_some_and(|limit| solutions.len() >= limit) {
+                    stopped_early = true;
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        )?;
+        return Ok(Outcome::All {
+            solutions,
+            stopped_early,
+        });
+    }
+
+    let solution = backtrack::backtrack(
+        *initial_board,
+        placements,
+        placement_indices,
+        ctx.order,
+        &ctx.limits,
+        stats,
+    )?;
+    Ok(Outcome::First(solution))
+}
+
+fn report_date<B: BitBoard>(
+    ctx: &Context<B>,
+    args: &Args,
+    job: &Job<B>,
+    outcome: Outcome,
+) -> Result<(), Error> {
+    let date = job.date;
+    let no_solution = || Error::NoSolution(format!("No solution found for {date} :("));
+
+    match outcome {
+        Outcome::Count { count, distinct } => {
+            let numeric = date.numeric();
+            match distinct {
+                Some(distinct) => {
+                    println!("{numeric}: {count} solutions, {distinct} distinct up to symmetry")
+                }
+                None => println!("{numeric}: {count} solutions"),
+            }
+            if count == 0 {
+                return Err(no_solution());
+            }
+        }
+        Outcome::All {
+            solutions,
+            stopped_early,
+        } => {
+            // Every solution covers the whole board, so each decode fully overwrites the previous
+            // one
+            let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
+            let heading = |index: usize| format!("Solution {} of {}:", index + 1, solutions.len());
+            if let Some(columns) = args.columns {
+                let blocks = solutions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, solution)| {
+                        block(
+                            &heading(index),
+                            &mut decoding_board,
+                            &ctx.solution(job, solution),
+                            args.format,
+                            &ctx.tiles,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                write!(
+                    io::stdout(),
+                    "{}",
+                    render::columns(&blocks, columns, terminal_width())
+                )?;
+            } else {
+                write!(io::stdout(), "{}", args.format.prelude())?;
+                for (index, solution) in solutions.iter().enumerate() {
+                    if let Some(heading) = args.format.heading(&heading(index)) {
+                        write!(io::stdout(), "{heading}")?;
                     }
+                    print_solution(
+                        &mut decoding_board,
+                        &ctx.solution(job, solution),
+                        args.format,
+                        &ctx.tiles,
+                    )?;
+                }
+                write!(io::stdout(), "{}", args.format.epilogue())?;
+            }
 
-                    tile.rotate_ccw_90();
+            if args.format.is_text() {
+                if stopped_early {
+                    println!("{} shown (search stopped early)", solutions.len());
+                } else {
+                    println!("Found {} solutions for {}", solutions.len(), date);
                 }
+            }
+            if solutions.is_empty() {
+                return Err(no_solution());
+            }
+        }
+        Outcome::First(Some(solution)) => {
+            let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
 
-                tile.reflect_over_vert();
+            if let Some(output) = &args.output {
+                let contents = document(
+                    &mut decoding_board,
+                    &ctx.solution(job, &solution),
+                    args.format,
+                    &ctx.tiles,
+                );
+                let path = Output::new(output.clone()).write(date, args.format, &contents)?;
+                println!("Wrote {}", path.display());
+                return Ok(());
             }
 
-            tile_configs
+            write!(io::stdout(), "{}", args.format.prelude())?;
+            if let Some(heading) = args.format.heading(&format!("Solution for {date}:")) {
+                write!(io::stdout(), "{heading}")?;
+            }
+            print_solution(
+                &mut decoding_board,
+                &ctx.solution(job, &solution),
+                args.format,
+                &ctx.tiles,
+            )?;
+            write!(io::stdout(), "{}", args.format.epilogue())?;
+        }
+        Outcome::First(None) => return Err(no_solution()),
+    }
+
+    Ok(())
+}
+
+// Solves the job while drawing every step of the search
+fn animate<B: BitBoard>(ctx: &Context<B>, args: &Args, job: &Job<B>) -> Result<(), Error> {
+    let mut animation = Animation::new(
+        &ctx.encoding_board,
+        &job.placements,
+        job.placement_indices.len() - 1,
+        job.initial_board,
+        args.format,
+        animate::Options {
+            delay: args.animate_speed.unwrap_or(Duration::from_millis(50)),
+            fps: args.fps,
+            forward_only: args.forward_only,
+            max_frames: args.max_frames.unwrap_or(animate::DEFAULT_MAX_FRAMES),
+        },
+    );
+    animation.draw();
+
+    let solution = backtrack::backtrack(
+        job.initial_board,
+        &job.placements,
+        &job.placement_indices,
+        ctx.order,
+        &ctx.limits,
+        &mut animation,
+    )?;
+    // The last few steps may have been skipped to keep to the frame rate
+    let steps = animation.steps;
+    if let Some(image) = animation.finish() {
+        let output = Output::new(
+            args.output
+                .clone()
+                .expect("images are only animated to a file"),
+        );
+        let path = output.write(job.date, args.format, &image)?;
+        println!("Wrote {}", path.display());
+    }
+
+    match solution {
+        Some(_) => {
+            println!("Solved {} in {steps} steps", job.date);
+            Ok(())
+        }
+        None => Err(Error::NoSolution(format!(
+            "No solution found for {} :(",
+            job.date
+        ))),
+    }
+}
+
+fn list_tiles(tiles: &[Tile], transforms: Transforms) {
+    for (id, tile) in tiles.iter().enumerate() {
+        if id > 0 {
+            println!();
+        }
+        let allowed = transforms.min(tile.allowed_transforms());
+        println!(
+            "Tile {id}: {} cells, {} distinct orientations out of {}",
+            tile.area(),
+            tile.orientations(transforms).len(),
+            allowed.sides() * allowed.rotations()
+        );
+        for line in render::tile(tile).lines() {
+            println!("  {line}");
+        }
+    }
+}
+
+// Printed to stderr so that it can't interfere with machine-readable output
+fn print_stats<B: BitBoard>(job: &Job<B>, order: Order, stats: &SearchStats, elapsed: Duration) {
+    let per_piece = job
+        .placement_indices
+        .windows(2)
+        .map(|range| (range[1] - range[0]).to_string())
+        .collect::<Vec<_>>();
+
+    eprintln!("Statistics:");
+    eprintln!("  Search order:         {}", order.name());
+    eprintln!("  Placements per piece: {}", per_piece.join(", "));
+    eprintln!("  Placement attempts:   {}", stats.attempts);
+    // Pieces the search never reached made no attempts
+    let attempts = (0..per_piece.len())
+        .map(|piece| {
+            stats
+                .piece_attempts
+                .get(piece)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
         })
         .collect::<Vec<_>>();
+    eprintln!("  Attempts per piece:   {}", attempts.join(", "));
+    eprintln!("  Backtracks:           {}", stats.backtracks);
+    eprintln!(
+        "  Maximum depth:        {} of {}",
+        stats.max_depth,
+        per_piece.len()
+    );
+    eprintln!("  Elapsed:              {elapsed:?}");
+}
+
+fn verify_solution<B: BitBoard>(
+    ctx: &Context<B>,
+    tiles: &[Tile],
+    path: &Path,
+    date: Date,
+) -> Result<(), Error> {
+    let pieces = load(path, verify::parse_solution)?;
+    let date_points = ctx.encoding_board.date_points(date);
 
-    const MONTH: Month = Month::October;
-    const DAY: i32 = 13;
+    match verify::verify(
+        &ctx.encoding_board,
+        tiles,
+        &date_points,
+        &pieces,
+        ctx.transforms,
+    ) {
+        Ok(()) => {
+            println!("The solution for {date} is valid");
+            Ok(())
+        }
+        Err(problems) => {
+            let mut message = format!("The solution for {date} is not valid:");
+            for problem in problems {
+                message.push_str("\n  ");
+                message.push_str(&problem);
+            }
+            Err(Error::Rejected(message))
+        }
+    }
+}
 
-    let month_num = MONTH as i32;
-    let month_point = Point {
-        x: month_num % 6,
-        y: 6 - month_num / 6,
+// Reveals the position of one more tile that extends the placed pieces to a solution, choosing the
+// tile with the fewest positions left. At a dead end, blames the latest piece that must move.
+fn hint<B: BitBoard>(ctx: &Context<B>, tiles: &[Tile], args: &Args) -> Result<(), Error> {
+    let date = resolve_date(args);
+    check_date(date, args.force)?;
+
+    let pieces = match &args.placed {
+        Some(path) => load(path, verify::parse_solution)?,
+        None => Vec::new(),
+    };
+    let placed = place_pieces(ctx, tiles, date, &pieces)?;
+
+    let job = ctx.partial_job(date, &placed)?;
+    let solve = |job: &Job<B>| {
+        backtrack::backtrack(
+            job.initial_board,
+            &job.placements,
+            &job.placement_indices,
+            ctx.order,
+            &ctx.limits,
+            &mut (),
+        )
     };
 
-    let day_point = Point {
-        x: (DAY - 1) % 7,
-        y: 4 - (DAY - 1) / 7,
+    let Some(solution) = solve(&job)? else {
+        // Dropping pieces from the end until the rest can be extended finds the piece which made
+        // the placement a dead end
+        for count in (0..placed.len()).rev() {
+            if solve(&ctx.partial_job(date, &placed[..count])?)?.is_some() {
+                return Err(Error::NoSolution(format!(
+                    "The placed pieces can't be extended to a solution for {date}; piece {} \
+                     must move",
+                    pieces[count].label
+                )));
+            }
+        }
+        return Err(Error::NoSolution(format!(
+            "No solution found for {date} :("
+        )));
+    };
+
+    let occupied = placed
+        .iter()
+        .fold(job.initial_board, |board, &(_, enc)| board | enc);
+    let hint = (0..tiles.len())
+        .filter(|&tile| placed.iter().all(|&(placed, _)| placed != tile))
+        .min_by_key(|&tile| {
+            ctx.configurations[tile]
+                .iter()
+                .filter(|&&enc| enc.is_disjoint(occupied))
+                .count()
+        });
+
+    match hint {
+        Some(tile) => {
+            let enc = job.placements[solution[tile]];
+            let mut points = ctx
+                .encoding_board
+                .decode_points(enc)
+                .expect("placements are encoded on the board");
+            points.sort_by_key(|point| (point.x, point.y));
+
+            print!("Hint for {date}: place tile {tile} at");
+            for point in points {
+                print!(" ({},{})", point.x, point.y);
+            }
+            println!();
+        }
+        None => println!("Every tile is already placed, and the solution for {date} is valid"),
+    }
+
+    Ok(())
+}
+
+// Enumerates every solution for the date and groups them by which tile covers `cell`, printing
+// how many solutions each tile has there along with the first of them
+fn by_cell<B: BitBoard>(ctx: &Context<B>, args: &Args, cell: Point) -> Result<(), Error> {
+    let date = resolve_date(args);
+    check_date(date, args.force)?;
+    let enc = ctx
+        .encoding_board
+        .encode_checked([cell])
+        .map_err(|error| Error::InvalidInput(error.to_string()))?;
+
+    let job = ctx.job(date, None)?;
+    // The number of solutions and the first one found, keyed by the tile covering the cell
+    let mut groups = HashMap::<Option<usize>, (u64, Vec<usize>)>::new();
+    backtrack::search(
+        job.initial_board,
+        &job.placements,
+        &job.placement_indices,
+        ctx.order,
+        &ctx.limits,
+        &mut (),
+        |solution| {
+            let tile = job.tile_covering(solution, enc);
+            groups
+                .entry(tile)
+                .or_insert_with(|| (0, solution.to_vec()))
+                .0 += 1;
+            ControlFlow::Continue(())
+        },
+    )?;
+    if groups.is_empty() {
+        return Err(Error::NoSolution(format!(
+            "No solution found for {date} :("
+        )));
+    }
+    // In tile order, with the solutions leaving the cell uncovered last
+    let mut groups = groups.into_iter().collect::<Vec<_>>();
+    groups.sort_by_key(|&(tile, _)| (tile.is_none(), tile));
+
+    let total = groups.iter().map(|(_, (count, _))| count).sum::<u64>();
+    println!(
+        "The {total} solutions for {date}, by what covers ({}, {}):",
+        cell.x, cell.y
+    );
+    let group_name = |tile: Option<usize>| match tile {
+        Some(tile) => format!("Tile {tile}"),
+        None => "Nothing".to_owned(),
     };
+    for &(tile, (count, _)) in &groups {
+        println!("  {:<8} {count}", group_name(tile));
+    }
 
-    let initial_board = encoding_board
-        .encode([month_point, day_point].into_iter())
-        .expect("Month and day should lie within the board");
+    let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
+    for (tile, (count, example)) in &groups {
+        println!();
+        println!(
+            "{} covers it in {count} {}, such as:",
+            group_name(*tile),
+            if *count == 1 { "solution" } else { "solutions" }
+        );
+        print_solution(
+            &mut decoding_board,
+            &ctx.solution(&job, example),
+            args.format,
+            &ctx.tiles,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Matches each piece to the tile with its shape, returning `(tile index, placement)` pairs for
+// `Context::partial_job`
+fn place_pieces<B: BitBoard>(
+    ctx: &Context<B>,
+    tiles: &[Tile],
+    date: Date,
+    pieces: &[Piece],
+) -> Result<Vec<(usize, B)>, Error> {
+    let date_points = ctx.encoding_board.date_points(date);
+    let assigned = verify::verify_partial(
+        &ctx.encoding_board,
+        tiles,
+        &date_points,
+        pieces,
+        ctx.transforms,
+    )
+    .map_err(|problems| {
+        let mut message = format!("The placed pieces don't fit {date}:");
+        for problem in problems {
+            message.push_str("\n  ");
+            message.push_str(&problem);
+        }
+        Error::Rejected(message)
+    })?;
+
+    Ok(pieces
+        .iter()
+        .zip(assigned)
+        .map(|(piece, tile)| {
+            let enc = ctx
+                .encoding_board
+                .encode(piece.points.iter().copied())
+                .expect("placed pieces were checked to lie on the board");
+            (tile, enc)
+        })
+        .collect())
+}
+
+// Finishes a partly filled board, keeping the pieces already placed where they are. The date
+// comes from the `#` cells unless it's given explicitly.
+fn solve_partial<B: BitBoard>(ctx: &Context<B>, tiles: &[Tile], args: &Args) -> Result<(), Error> {
+    let path = args
+        .solution
+        .as_deref()
+        .expect("solve-partial always has a board path");
+    let (pieces, marked) = load(path, verify::parse_partial)?;
+
+    let date = if args.month.is_some() || args.day.is_some() || marked.is_empty() {
+        resolve_date(args)
+    } else {
+        ctx.encoding_board
+            .date_at(&marked)
+            .map_err(Error::InvalidInput)?
+    };
+    check_date(date, args.force)?;
 
-    let mut placements = Vec::new();
-    let mut placement_indices = Vec::with_capacity(configurations.len() + 1);
+    let placed = place_pieces(ctx, tiles, date, &pieces)?;
+    let job = ctx.partial_job(date, &placed)?;
+    let solution = backtrack::backtrack(
+        job.initial_board,
+        &job.placements,
+        &job.placement_indices,
+        ctx.order,
+        &ctx.limits,
+        &mut (),
+    )?
+    .ok_or_else(|| {
+        Error::NoSolution(format!(
+            "The placed pieces can't be extended to a solution for {date}"
+        ))
+    })?;
 
-    for configs in configurations {
-        placement_indices.push(placements.len());
+    // The solver's pieces get labels the drawing doesn't use yet, preferring their tile numbers
+    let mut labels = vec![None; tiles.len()];
+    for (piece, &(tile, _)) in pieces.iter().zip(&placed) {
+        labels[tile] = piece.label.chars().next();
+    }
+    let solver_placed = (0..tiles.len())
+        .filter(|&tile| labels[tile].is_none())
+        .collect::<Vec<_>>();
+    for &tile in &solver_placed {
+        let own = char::from_digit(tile as u32, 10).into_iter();
+        labels[tile] = own
+            .chain('a'..='z')
+            .chain('A'..='Z')
+            .find(|&label| !labels.contains(&Some(label)));
+    }
 
-        for enc in configs.into_iter().filter(|&enc| enc & initial_board == 0) {
-            placements.push(enc);
+    let mut cells = HashMap::new();
+    for (tile, &index) in solution.iter().enumerate() {
+        let points = ctx
+            .encoding_board
+            .decode_points(job.placements[index])
+            .expect("placements are encoded on the board");
+        for point in points {
+            cells.insert(point, labels[tile].unwrap_or('?'));
         }
     }
+    for point in ctx.encoding_board.date_points(date) {
+        cells.insert(point, '#');
+    }
+
+    println!("Finished board for {date}:");
+    let points = ctx.encoding_board.points().collect::<Vec<_>>();
+    let bounds = ctx.encoding_board.bounds();
+    for y in (bounds.min().y..=bounds.max().y).rev() {
+        let line = (bounds.min().x..=bounds.max().x)
+            .map(|x| {
+                let point = Point { x, y };
+                match cells.get(&point) {
+                    Some(&label) => label,
+                    None if points.contains(&point) => '.',
+                    None => ' ',
+                }
+            })
+            .collect::<String>();
+        println!("{}", line.trim_end());
+    }
+
+    let names = |labels: Vec<char>| {
+        if labels.is_empty() {
+            return "none".to_owned();
+        }
+        labels
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let yours = pieces
+        .iter()
+        .flat_map(|piece| piece.label.chars().next())
+        .collect();
+    let solvers = solver_placed
+        .iter()
+        .flat_map(|&tile| labels[tile])
+        .collect();
+    println!("Placed by you: {}", names(yours));
+    println!("Placed by the solver: {}", names(solvers));
 
-    placement_indices.push(placements.len());
+    Ok(())
+}
+
+// Everything the per-date solvers need, built once up front and shared between dates
+struct Context<B = u64> {
+    encoding_board: EncodingBoard<B>,
+    // The tiles in the order of their ids, which `configurations` follows too
+    tiles: Vec<Tile>,
+    configurations: Vec<BTreeSet<B>>,
+    // When searches give up
+    limits: Limits,
+    threads: usize,
+    // Whether to also solve dates that don't exist in the calendar, like February 30
+    force: bool,
+    // The orientations tiles may be placed in
+    transforms: Transforms,
+    // The strategy every search uses
+    order: Order,
+    // Whether to hide the progress display for batch commands
+    quiet: bool,
+}
 
-    let solution = backtrack::backtrack(initial_board, &placements, &placement_indices);
+// The search inputs for a single date
+struct Job<B = u64> {
+    date: Date,
+    initial_board: B,
+    placements: Vec<B>,
+    placement_indices: Vec<usize>,
+}
 
-    if let Some(solution) = solution {
-        let mut decoding_board = DecodingBoard::from(encoding_board);
+impl<B: BitBoard> Job<B> {
+    // The tile covering the cell in the solution, or `None` if the cell is left uncovered
+    fn tile_covering(&self, solution: &[usize], cell: B) -> Option<usize> {
         solution
+            .iter()
+            .position(|&index| !self.placements[index].is_disjoint(cell))
+    }
+}
+
+impl<B: BitBoard> Context<B> {
+    // Applies the options which can change between commands without rebuilding the configurations
+    fn configure(&mut self, args: &Args) {
+        self.limits = Limits::deadline(args.timeout.map(|timeout| Instant::now() + timeout));
+        self.threads = args.threads.unwrap_or_else(parallel::default_threads);
+        self.force = args.force;
+        self.quiet = args.quiet;
+        self.order = args.order;
+    }
+
+    // The solution's placements on the board, from indices into the job's placements
+    fn solution(&self, job: &Job<B>, solution: &[usize]) -> Solution<B> {
+        let tiles = solution
+            .iter()
+            .map(|&index| job.placements[index])
+            .collect::<Vec<_>>();
+
+        // Cells besides the date's can only stay uncovered when tiles were left out, and then the
+        // tiles cover fewer cells than are free
+        let covered = tiles
+            .iter()
+            .fold(job.initial_board, |board, &enc| board | enc);
+        let area = tiles.iter().map(|enc| enc.count_ones()).sum::<u32>();
+        debug_assert!(
+            covered == self.encoding_board.full_mask()
+                || area < self.encoding_board.free_cells(job.initial_board),
+            "the solution for {} leaves cells uncovered",
+            job.date
+        );
+
+        Solution::new(&self.encoding_board, tiles, job.initial_board)
+    }
+
+    fn encode_date(&self, date: Date) -> Result<B, Error> {
+        self.encoding_board
+            .encode_checked(self.encoding_board.date_points(date))
+            .map_err(|error| {
+                Error::InvalidInput(format!("{date} does not lie on the board: {error}"))
+            })
+    }
+
+    // The dates batch commands should solve
+    fn dates(&self) -> impl Iterator<Item = Date> + '_ {
+        Date::board_dates().filter(|date| self.force || date.is_valid())
+    }
+
+    fn job(&self, date: Date, rng: Option<&mut Rng>) -> Result<Job<B>, Error> {
+        let initial_board = self.encode_date(date)?;
+        let (mut placements, placement_indices) =
+            filter_placements(&self.configurations, initial_board);
+        if let Some(rng) = rng {
+            shuffle_placements(&mut placements, &placement_indices, rng);
+        }
+
+        Ok(Job {
+            date,
+            initial_board,
+            placements,
+            placement_indices,
+        })
+    }
+
+    // Like `job`, but with some tiles fixed in place, given as `(tile index, placement)` pairs
+    fn partial_job(&self, date: Date, placed: &[(usize, B)]) -> Result<Job<B>, Error> {
+        let initial_board = self.encode_date(date)?;
+        let configurations = self
+            .configurations
             .iter()
             .enumerate()
-            .map(|(id, &index)| (id as u8, placements[index]))
-            .for_each(|(id, enc)| decoding_board.decode(enc, Some(id)));
-        decoding_board.decode(initial_board, None);
-        decoding_board.print();
+            .map(
+                |(index, configs)| match placed.iter().find(|&&(tile, _)| tile == index) {
+                    Some(&(_, enc)) => BTreeSet::from([enc]),
+                    None => configs.clone(),
+                },
+            )
+            .collect::<Vec<_>>();
+        let (placements, placement_indices) = filter_placements(&configurations, initial_board);
+
+        Ok(Job {
+            date,
+            initial_board,
+            placements,
+            placement_indices,
+        })
+    }
+
+    // Finds the first solution for each job, spreading the work across `self.threads` threads
+    fn solve_all(&self, jobs: &[Job<B>]) -> Result<Vec<Option<Vec<usize>>>, Error> {
+        self.run_all(jobs, |job| {
+            backtrack::backtrack(
+                job.initial_board,
+                &job.placements,
+                &job.placement_indices,
+                self.order,
+                &self.limits,
+                &mut (),
+            )
+        })
+    }
+
+    // Counts the solutions for each job, spreading the work across `self.threads` threads
+    fn count_all(&self, jobs: &[Job<B>]) -> Result<Vec<u64>, Error> {
+        self.run_all(jobs, |job| {
+            backtrack::count(
+                job.initial_board,
+                &job.placements,
+                &job.placement_indices,
+                self.order,
+                &self.limits,
+                &mut (),
+            )
+        })
+    }
+
+    // Counts the solutions for each job, giving up on a job once it has `limit` of them
+    fn count_all_up_to(&self, jobs: &[Job<B>], limit: u64) -> Result<Vec<u64>, Error> {
+        self.run_all(jobs, |job| {
+            backtrack::count_up_to(
+                job.initial_board,
+                &job.placements,
+                &job.placement_indices,
+                limit,
+                self.order,
+                &self.limits,
+                &mut (),
+            )
+        })
+    }
+
+    // Runs a search for every job in parallel. All batch commands go through here, so this is
+    // where progress is reported.
+    fn run_all<R: Send>(
+        &self,
+        jobs: &[Job<B>],
+        search: impl Fn(&Job<B>) -> Result<R, Interrupted> + Sync,
+    ) -> Result<Vec<R>, Error> {
+        let progress = Progress::new(jobs.len(), self.quiet);
+
+        parallel::map(jobs, self.threads, |job| {
+            let result = search(job);
+            progress.tick();
+            result
+        })
+        .into_iter()
+        .map(|result| result.map_err(Error::from))
+        .collect()
+    }
+}
+
+// Reads and parses a definition file
+fn load<T>(path: &Path, parse: fn(&str) -> Result<T, String>) -> Result<T, Error> {
+    fs::read_to_string(path)
+        .map_err(|error| format!("failed to read `{}`: {error}", path.display()))
+        .and_then(|src| parse(&src))
+        .map_err(Error::InvalidInput)
+}
+
+fn resolve_date(args: &Args) -> Date {
+    let mut date = Date::resolve(args.month, args.day, Date::today());
+    if args.year.is_some() {
+        date.year = args.year;
+    }
+    date
+}
+
+// Rejects dates that aren't in the calendar unless `force` is set
+fn check_date(date: Date, force: bool) -> Result<(), Error> {
+    if force {
+        return Ok(());
+    }
+
+    date.validate()
+        .map_err(|error| Error::InvalidInput(format!("{error} (use --force to solve it anyway)")))
+}
+
+// Shuffles the placements of each tile among themselves, so that the search explores them in a
+// different order while each tile's range stays contiguous
+fn shuffle_placements<B: BitBoard>(
+    placements: &mut [B],
+    placement_indices: &[usize],
+    rng: &mut Rng,
+) {
+    for range in placement_indices.windows(2) {
+        rng.shuffle(&mut placements[range[0]..range[1]]);
+    }
+}
+
+// Solves every month/day combination on the board and reports the ones without a solution
+fn check_year<B: BitBoard>(ctx: &Context<B>) -> Result<(), Error> {
+    let jobs = ctx
+        .dates()
+        .map(|date| ctx.job(date, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let solutions = ctx.solve_all(&jobs)?;
+
+    let failures = jobs
+        .iter()
+        .zip(&solutions)
+        .filter(|(_, solution)| solution.is_none())
+        .map(|(job, _)| job.date)
+        .collect::<Vec<_>>();
+
+    let mode = match ctx.transforms {
+        Transforms::RotationsAndReflections => "",
+        Transforms::Rotations => " without flipping tiles",
+        Transforms::Identity => " without turning tiles",
+    };
+    println!("Checked {} dates{mode}", jobs.len());
+    println!("Solvable: {}", jobs.len() - failures.len());
+    println!("Unsolvable: {}", failures.len());
+    for date in &failures {
+        println!("  {date}");
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::NoSolution(format!(
+            "{} dates have no solution",
+            failures.len()
+        )))
+    }
+}
+
+// Times finding the first solution of every date. The dates are solved one after another so that
+// the timings aren't skewed by other threads competing for the CPU.
+fn bench<B: BitBoard>(ctx: &Context<B>, iterations: usize, csv: bool) -> Result<(), Error> {
+    let mut times = Vec::new();
+
+    if csv {
+        println!("date,order,microseconds,attempts");
+    } else {
+        println!("Search order: {}", ctx.order.name());
+        println!("{:<10} {:>14} {:>12}", "date", "time (us)", "attempts");
+    }
+
+    for date in ctx.dates() {
+        let job = ctx.job(date, None)?;
+
+        // Counting is cheap enough to leave on while timing. Every iteration does the same work,
+        // so the counts are kept from the last one.
+        let mut stats = SearchStats::default();
+        let started = Instant::now();
+        for _ in 0..iterations {
+            stats = SearchStats::default();
+            backtrack::backtrack(
+                job.initial_board,
+                &job.placements,
+                &job.placement_indices,
+                ctx.order,
+                &ctx.limits,
+                &mut stats,
+            )?;
+        }
+        let micros = started.elapsed().as_secs_f64() * 1e6 / iterations as f64;
+
+        if csv {
+            println!(
+                "{},{},{micros:.1},{}",
+                date.numeric(),
+                ctx.order.name(),
+                stats.attempts
+            );
+        } else {
+            println!(
+                "{:<10} {micros:>14.1} {:>12}",
+                date.numeric(),
+                stats.attempts
+            );
+        }
+        times.push(micros);
+    }
+
+    if csv || times.is_empty() {
+        return Ok(());
+    }
+
+    times.sort_by(f64::total_cmp);
+    let total = times.iter().sum::<f64>();
+    let median = if times.len() % 2 == 0 {
+        (times[times.len() / 2 - 1] + times[times.len() / 2]) / 2.0
+    } else {
+        times[times.len() / 2]
+    };
+
+    println!();
+    println!("{:<10} {:>14.1}", "min", times[0]);
+    println!("{:<10} {median:>14.1}", "median");
+    println!("{:<10} {:>14.1}", "mean", total / times.len() as f64);
+    println!("{:<10} {:>14.1}", "max", times[times.len() - 1]);
+    println!("{:<10} {total:>14.1}", "total");
+
+    Ok(())
+}
+
+// Reports the dates with the fewest solutions, followed by the ten hardest dates overall. Ties are
+// broken by calendar order so the output is stable.
+fn hardest<B: BitBoard>(ctx: &Context<B>) -> Result<(), Error> {
+    let jobs = ctx
+        .dates()
+        .map(|date| ctx.job(date, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let counts = ctx.count_all(&jobs)?;
+
+    let mut ranking = jobs
+        .iter()
+        .map(|job| job.date)
+        .zip(counts)
+        .collect::<Vec<_>>();
+    // Sorting is stable, so dates with equal counts stay in calendar order
+    ranking.sort_by_key(|&(_, count)| count);
+
+    let Some(&(_, fewest)) = ranking.first() else {
+        return Ok(());
+    };
+    println!("Fewest solutions: {fewest}");
+    for (date, _) in ranking.iter().take_while(|&&(_, count)| count == fewest) {
+        println!("  {date}");
+    }
+
+    println!();
+    println!("Hardest dates:");
+    for (rank, (date, count)) in ranking.iter().take(10).enumerate() {
+        println!(
+            "{:>4}. {:<14}{count:>6} solutions",
+            rank + 1,
+            date.to_string()
+        );
+    }
+
+    Ok(())
+}
+
+// Lists the dates whose solution is forced. Dates without any solution are listed separately, since
+// they mean the puzzle itself is broken.
+fn unique<B: BitBoard>(ctx: &Context<B>) -> Result<(), Error> {
+    let jobs = ctx
+        .dates()
+        .map(|date| ctx.job(date, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let counts = ctx.count_all_up_to(&jobs, 2)?;
+
+    let with_count = |wanted| {
+        jobs.iter()
+            .zip(&counts)
+            .filter(move |&(_, &count)| count == wanted)
+            .map(|(job, _)| job.date)
+            .collect::<Vec<_>>()
+    };
+    let unique = with_count(1);
+    let unsolvable = with_count(0);
+
+    if unique.is_empty() {
+        println!("No date has exactly one solution");
     } else {
-        println!("No solution found :(");
+        println!("Dates with exactly one solution:");
+        for date in &unique {
+            println!("  {date}");
+        }
+    }
+
+    if !unsolvable.is_empty() {
+        println!();
+        println!("Dates with no solution at all:");
+        for date in &unsolvable {
+            println!("  {date}");
+        }
     }
+
+    Ok(())
+}
+
+// Prints a grid of solution counts with a row per month and a column per day
+fn heatmap<B: BitBoard>(ctx: &Context<B>, filter: Option<Month>) -> Result<(), Error> {
+    let jobs = ctx
+        .dates()
+        .filter(|date| filter.is_none_or(|month| date.month == month))
+        .map(|date| ctx.job(date, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let counts = ctx.count_all(&jobs)?;
+
+    print!("   ");
+    for day in 1..=31 {
+        print!("{day:>5}");
+    }
+    println!();
+
+    for month in Month::ALL {
+        if filter.is_some_and(|filter| filter != month) {
+            continue;
+        }
+
+        print!("{}", month.abbreviation());
+        for day in 1..=31 {
+            // Dates which aren't in the calendar are left blank
+            let count = jobs
+                .iter()
+                .position(|job| job.date.month == month && job.date.day == day)
+                .map(|index| counts[index]);
+
+            match count {
+                None => print!("{:>5}", ""),
+                Some(0) => print!("{:>5}", "--"),
+                Some(count @ 1..=999) => print!("{count:>5}"),
+                Some(_) => print!("{:>5}", "999+"),
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+// Prints one solution for every date in a range
+fn solve_range<B: BitBoard>(
+    ctx: &Context<B>,
+    dates: Vec<Date>,
+    format: Format,
+    columns: Option<usize>,
+    output: Option<&Output>,
+    mut rng: Option<&mut Rng>,
+) -> Result<(), Error> {
+    let dates = dates
+        .into_iter()
+        .filter(|date| ctx.force || date.is_valid());
+
+    // Shuffling happens here rather than on the workers so the seed gives the same results
+    // regardless of how many threads there are
+    let jobs = dates
+        .map(|date| ctx.job(date, rng.as_deref_mut()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let solutions = ctx.solve_all(&jobs)?;
+
+    let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
+    let mut failures = 0;
+    let mut written = 0;
+
+    if let Some(columns) = columns {
+        let blocks = jobs
+            .iter()
+            .zip(&solutions)
+            .map(|(job, solution)| {
+                let heading = format!("=== {} ===", job.date);
+                match solution {
+                    Some(solution) => block(
+                        &heading,
+                        &mut decoding_board,
+                        &ctx.solution(job, solution),
+                        format,
+                        &ctx.tiles,
+                    ),
+                    None => {
+                        failures += 1;
+                        format!("{heading}\nNo solution found :(\n")
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        print!("{}", render::columns(&blocks, columns, terminal_width()));
+    } else {
+        if output.is_none() {
+            write!(io::stdout(), "{}", format.prelude())?;
+        }
+        for (job, solution) in jobs.iter().zip(&solutions) {
+            if let Some(output) = output {
+                match solution {
+                    Some(solution) => {
+                        let contents = document(
+                            &mut decoding_board,
+                            &ctx.solution(job, solution),
+                            format,
+                            &ctx.tiles,
+                        );
+                        output.write(job.date, format, &contents)?;
+                        written += 1;
+                    }
+                    None => {
+                        failures += 1;
+                        println!("No solution found for {} :(", job.date);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(heading) = format.heading(&format!("=== {} ===", job.date)) {
+                write!(io::stdout(), "{heading}")?;
+            }
+
+            match solution {
+                Some(solution) => print_solution(
+                    &mut decoding_board,
+                    &ctx.solution(job, solution),
+                    format,
+                    &ctx.tiles,
+                )?,
+                None => {
+                    failures += 1;
+                    if format.is_text() {
+                        println!("No solution found :(");
+                    }
+                }
+            }
+        }
+
+        if output.is_some() {
+            println!("Wrote {written} files");
+        } else {
+            write!(io::stdout(), "{}", format.epilogue())?;
+        }
+    }
+
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(Error::NoSolution(format!(
+            "{failures} dates have no solution"
+        )))
+    }
+}
+
+// Solves each date read from stdin, one per line. Lines which can't be read are reported with
+// their line number without stopping the rest of the batch.
+fn solve_stdin<B: BitBoard>(
+    ctx: &Context<B>,
+    args: &Args,
+    mut rng: Option<&mut Rng>,
+) -> Result<(), Error> {
+    let input = io::read_to_string(io::stdin())
+        .map_err(|error| Error::InvalidInput(format!("failed to read stdin: {error}")))?;
+
+    // Each remaining line's number and text, along with the index of its job
+    let mut lines = Vec::new();
+    let mut jobs = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let job = cli::parse_date_line(line)
+            .map_err(Error::InvalidInput)
+            .and_then(|mut date| {
+                date.year = args.year;
+                check_date(date, ctx.force)?;
+                ctx.job(date, rng.as_deref_mut())
+            });
+        let job = job.map(|job| {
+            jobs.push(job);
+            jobs.len() - 1
+        });
+        lines.push((index + 1, line, job));
+    }
+
+    let solutions = ctx.solve_all(&jobs)?;
+
+    let mut decoding_board = DecodingBoard::from(&ctx.encoding_board);
+    let mut failures = 0;
+    let mut malformed = 0;
+
+    for (number, line, job) in lines {
+        let job = match job {
+            Ok(job) => job,
+            Err(error) => {
+                malformed += 1;
+                eprintln!("error: line {number}: {}", error.message());
+                if args.jsonl {
+                    println!(
+                        "{{\"line\":{number},\"input\":{},\"error\":{}}}",
+                        render::json_string(line),
+                        render::json_string(error.message())
+                    );
+                } else {
+                    println!("=== line {number}: {line} ===");
+                    println!("Skipped: {}", error.message());
+                }
+                continue;
+            }
+        };
+        let (job, solution) = (&jobs[job], &solutions[job]);
+
+        let format = if args.jsonl {
+            Format::Json
+        } else {
+            args.format
+        };
+        let rendering = solution.as_ref().map(|solution| {
+            let mut contents = Vec::new();
+            write_solution(
+                &mut contents,
+                &mut decoding_board,
+                &ctx.solution(job, solution),
+                format,
+                &ctx.tiles,
+            )
+            .expect("writing to a Vec can't fail");
+            String::from_utf8(contents).expect("renderings are valid UTF-8")
+        });
+        if rendering.is_none() {
+            failures += 1;
+        }
+
+        if args.jsonl {
+            println!(
+                "{{\"line\":{number},\"input\":{},\"date\":{},\"solution\":{}}}",
+                render::json_string(line),
+                render::json_string(&job.date.numeric()),
+                rendering.as_deref().map_or("null", str::trim_end)
+            );
+        } else {
+            println!("=== line {number}: {line} ===");
+            print!(
+                "{}",
+                rendering.as_deref().unwrap_or("No solution found :(\n")
+            );
+        }
+    }
+
+    if malformed > 0 {
+        Err(Error::InvalidInput(format!(
+            "{malformed} lines could not be read"
+        )))
+    } else if failures > 0 {
+        Err(Error::NoSolution(format!(
+            "{failures} dates have no solution"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn print_solution<B: BitBoard>(
+    decoding_board: &mut DecodingBoard,
+    solution: &Solution<B>,
+    format: Format,
+    tiles: &[Tile],
+) -> Result<(), Error> {
+    write_solution(
+        &mut io::stdout().lock(),
+        decoding_board,
+        solution,
+        format,
+        tiles,
+    )?;
+    Ok(())
+}
+
+// A solution written on its own, as to a file, with whatever the format needs around it
+fn document<B: BitBoard>(
+    decoding_board: &mut DecodingBoard,
+    solution: &Solution<B>,
+    format: Format,
+    tiles: &[Tile],
+) -> Vec<u8> {
+    let mut contents = format.prelude().as_bytes().to_vec();
+    write_solution(&mut contents, decoding_board, solution, format, tiles)
+        .expect("writing to a Vec can't fail");
+    contents.extend_from_slice(format.epilogue().as_bytes());
+    contents
+}
+
+// A solution under its heading, kept as a string to be laid out beside others with `--columns`
+fn block<B: BitBoard>(
+    heading: &str,
+    decoding_board: &mut DecodingBoard,
+    solution: &Solution<B>,
+    format: Format,
+    tiles: &[Tile],
+) -> String {
+    let contents = document(decoding_board, solution, format, tiles);
+    format!(
+        "{heading}\n{}",
+        String::from_utf8(contents).expect("text formats are valid UTF-8")
+    )
+}
+
+// The terminal's width, which is only known when the shell exports `COLUMNS`
+fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+fn write_solution<B: BitBoard>(
+    out: &mut impl Write,
+    decoding_board: &mut DecodingBoard,
+    solution: &Solution<B>,
+    format: Format,
+    tiles: &[Tile],
+) -> io::Result<()> {
+    format.render_solution_to(decoding_board, solution, out)?;
+
+    // Only possible with fewer tiles than the board needs, as with `--exclude-tile`
+    let mut extra = solution.extra_cells().collect::<Vec<_>>();
+    extra.sort_by_key(|point| (point.x, point.y));
+    if format.is_text() && !extra.is_empty() {
+        write!(out, "Also uncovered (shown as ?):")?;
+        for point in extra {
+            write!(out, " ({},{})", point.x, point.y)?;
+        }
+        writeln!(out)?;
+    }
+
+    if let Some(legend) = format.legend(decoding_board, tiles) {
+        write!(out, "\n{legend}")?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,105 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    backtrack::{Interrupted, Stats, Strategy, Watchdog},
+    bitboard::BitBoard,
+};
+
+// Marks a piece which isn't on the board yet
+const UNPLACED: usize = usize::MAX;
+
+// Places the piece with the fewest placements that still fit next, so that dead ends are found
+// as early as possible. Counting the options at every step costs more per step than a fixed
+// order, but usually visits far fewer boards.
+pub struct Mrv;
+
+impl Strategy for Mrv {
+    fn search<B: BitBoard>(
+        &self,
+        initial_board: B,
+        placements: &[B],
+        placement_indices: &[usize],
+        watchdog: &mut Watchdog<'_>,
+        stats: &mut impl Stats,
+        on_solution: &mut impl FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<(), Interrupted> {
+        let mut search = Search {
+            placements,
+            placement_indices,
+            watchdog,
+            stats,
+            on_solution,
+            solution: vec![UNPLACED; placement_indices.len() - 1],
+        };
+        // Whether the callback stopped the search or it ran out of options, it's over
+        let _ = search.visit(initial_board, 0)?;
+        Ok(())
+    }
+}
+
+struct Search<'a, 'w, B, S, F> {
+    placements: &'a [B],
+    placement_indices: &'a [usize],
+    watchdog: &'a mut Watchdog<'w>,
+    stats: &'a mut S,
+    on_solution: &'a mut F,
+    // The placement index of each piece, or `UNPLACED`
+    solution: Vec<usize>,
+}
+
+impl<B: BitBoard, S: Stats, F: FnMut(&[usize]) -> ControlFlow<()>> Search<'_, '_, B, S, F> {
+    fn visit(&mut self, board: B, depth: usize) -> Result<ControlFlow<()>, Interrupted> {
+        self.watchdog.check(self.stats)?;
+
+        if depth == self.solution.len() {
+            self.stats.solution();
+            return Ok((self.on_solution)(&self.solution));
+        }
+
+        // The unplaced piece with the fewest placements that fit. Any piece with none means this
+        // board is a dead end.
+        let mut best: Option<(usize, usize)> = None;
+        for piece in 0..self.solution.len() {
+            if self.solution[piece] != UNPLACED {
+                continue;
+            }
+
+            let range = self.placement_indices[piece]..self.placement_indices[piece + 1];
+            let mut options = 0;
+            for &placement in &self.placements[range] {
+                self.stats.attempt(piece);
+                if placement.is_disjoint(board) {
+                    options += 1;
+                }
+            }
+
+            if options == 0 {
+                return Ok(ControlFlow::Continue(()));
+            }
+            if best.is_none_or(|(_, fewest)| options < fewest) {
+                best = Some((piece, options));
+            }
+        }
+        let (piece, _) = best.expect("some piece is unplaced");
+
+        for index in self.placement_indices[piece]..self.placement_indices[piece + 1] {
+            let placement = self.placements[index];
+            if !placement.is_disjoint(board) {
+                continue;
+            }
+
+            self.solution[piece] = index;
+            self.stats.place(piece, index);
+            self.stats.depth(depth + 1);
+            let flow = self.visit(board | placement, depth + 1)?;
+            self.stats.backtrack(piece);
+            self.solution[piece] = UNPLACED;
+
+            if flow.is_break() {
+                return Ok(flow);
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+}
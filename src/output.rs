@@ -0,0 +1,54 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{date::Date, error::Error, render::Format};
+
+// Where `--output` writes each date's rendering. A path containing `{` is a template where
+// `{month}`, `{day}` and `{date}` are filled in, and any other path is a directory that gets one
+// file per date.
+pub struct Output {
+    template: String,
+}
+
+impl Output {
+    pub fn new(template: String) -> Self {
+        Self { template }
+    }
+
+    pub fn path(&self, date: Date, format: Format) -> PathBuf {
+        if !self.template.contains('{') {
+            return Path::new(&self.template).join(format!(
+                "{}.{}",
+                date.numeric(),
+                format.extension()
+            ));
+        }
+
+        let path = self
+            .template
+            .replace("{month}", &format!("{:02}", date.month as i32 + 1))
+            .replace("{day}", &format!("{:02}", date.day))
+            .replace("{date}", &date.numeric());
+        PathBuf::from(path)
+    }
+
+    // Writes the rendering for the date, creating any missing directories, and returns the path
+    // written to
+    pub fn write(&self, date: Date, format: Format, contents: &[u8]) -> Result<PathBuf, Error> {
+        let path = self.path(date, format);
+        let failed =
+            |error| Error::InvalidInput(format!("failed to write `{}`: {error}", path.display()));
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(failed)?;
+        }
+        fs::write(&path, contents).map_err(failed)?;
+
+        Ok(path)
+    }
+}
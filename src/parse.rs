@@ -0,0 +1,145 @@
+use crate::{
+    bitboard::BitBoard,
+    date::Month,
+    entity::{EncodingBoard, Label, Point, Tile, TileParseError, Transforms},
+};
+
+// Parses a board drawn as an ASCII grid, in the form `EncodingBoard::from_ascii` reads. The
+// drawing may be followed by a `[labels]` line and then an `X,Y LABEL` line for each labeled cell,
+// where a number from 1 to 31 is a day, a month is given by its name or abbreviation, and any
+// other text is kept as it is. Labels are placed on the drawing's coordinates, and then the board
+// is moved to start at the origin, in case the drawing has empty columns on its left.
+pub fn parse_board<B: BitBoard>(src: &str) -> Result<EncodingBoard<B>, String> {
+    let lines = src.lines().collect::<Vec<_>>();
+    let split = lines
+        .iter()
+        .position(|line| line.trim() == "[labels]")
+        .unwrap_or(lines.len());
+    let mut board =
+        EncodingBoard::from_ascii(&lines[..split].join("\n")).map_err(|error| error.to_string())?;
+
+    for (line_idx, line) in lines.iter().enumerate().skip(split + 1) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let line_num = line_idx + 1;
+        let (point, label) = parse_label(line).ok_or_else(|| {
+            format!("invalid label `{line}` on line {line_num}, expected X,Y LABEL")
+        })?;
+        if let Some(other) = board.point_of(&label) {
+            return Err(format!(
+                "{label} is already on ({}, {}), but line {line_num} puts it on ({}, {}) too",
+                other.x, other.y, point.x, point.y
+            ));
+        }
+        if let Some(other) = board.label_at(point) {
+            return Err(format!(
+                "({}, {}) is already labeled {other}, but line {line_num} labels it {label} too",
+                point.x, point.y
+            ));
+        }
+        board
+            .set_label(point, label)
+            .map_err(|error| format!("{error}, on line {line_num}"))?;
+    }
+
+    Ok(board.normalized().0)
+}
+
+fn parse_label(line: &str) -> Option<(Point, Label)> {
+    let (cell, text) = line.split_once(char::is_whitespace)?;
+    let (x, y) = cell.split_once(',')?;
+    let point = Point {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    };
+
+    let text = text.trim();
+    let label = match text.parse::<u8>() {
+        Ok(day) if (1..=31).contains(&day) => Label::Day(day),
+        _ => match text.parse::<Month>() {
+            Ok(month) => Label::Month(month),
+            Err(..) => Label::Custom(text.to_owned()),
+        },
+    };
+    Some((point, label))
+}
+
+// Parses tiles drawn as ASCII grids separated by blank lines, in the form `Tile::from_ascii`
+// reads. A drawing may start with a `[one-sided]` line for a tile which can't be flipped over, or
+// a `[fixed]` line for one which can't be turned at all. Lines starting with `//` are comments,
+// and end a drawing like a blank line does.
+pub fn parse_tiles(src: &str) -> Result<Vec<Tile>, String> {
+    let mut tiles = Vec::new();
+    // The lines of the tile currently being read along with their line numbers
+    let mut block = Vec::new();
+
+    for (line_idx, line) in src.lines().chain([""]).enumerate() {
+        let comment = line.trim_start().starts_with("//");
+        if !comment && !line.trim().is_empty() {
+            block.push((line_idx + 1, line));
+            continue;
+        }
+
+        if block.is_empty() {
+            continue;
+        }
+
+        let tile_idx = tiles.len();
+        let (transforms, rows) = match block[0].1.trim() {
+            "[one-sided]" => (Some(Transforms::Rotations), &block[1..]),
+            "[fixed]" => (Some(Transforms::Identity), &block[1..]),
+            _ => (None, &block[..]),
+        };
+        let drawing = rows
+            .iter()
+            .map(|&(_, line)| line)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut tile = Tile::from_ascii(&drawing).map_err(|error| match error {
+            TileParseError::UnexpectedChar { ch, line, column } => format!(
+                "unexpected character `{ch}` in tile {tile_idx} on line {} column {column}, expected `#`, `X`, `.`, or a space",
+                rows[line - 1].0
+            ),
+            TileParseError::NoFilledCells => format!(
+                "tile {tile_idx} starting on line {} has no filled cells",
+                block[0].0
+            ),
+            TileParseError::Disconnected { .. } => {
+                format!("in tile {tile_idx} starting on line {}, {error}", block[0].0)
+            }
+        })?;
+        if let Some(transforms) = transforms {
+            tile.set_allowed_transforms(transforms);
+        }
+        tiles.push(tile);
+        block.clear();
+    }
+
+    if tiles.is_empty() {
+        return Err("no tiles were found".to_owned());
+    }
+
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_separate_tiles() {
+        let src = "// Set 1\n##\n// a domino, then a monomino\n#\n\n// Set 2\n";
+        let tiles = parse_tiles(src).unwrap();
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[0].area(), 2);
+        assert_eq!(tiles[1].area(), 1);
+        assert_eq!(
+            parse_tiles("// nothing here\n"),
+            Err("no tiles were found".to_owned())
+        );
+    }
+}
@@ -0,0 +1,446 @@
+// A small PNG encoder for RGB images, so that `--format png` doesn't need an image library. The
+// pixels are compressed with fixed Huffman codes, only looking for runs that repeat the pixel to
+// the left or the row above, which is most of a picture made of flat rectangles.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// Deflate's length codes from 257, as the shortest length each stands for and how many extra bits
+// follow it
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+// Deflate's distance codes, as the shortest distance each stands for and how many extra bits
+// follow it
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+const MAX_DISTANCE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+// Encodes `pixels`, given row by row from the top, as a PNG image
+pub fn encode(width: u32, height: u32, pixels: &[[u8; 3]]) -> Vec<u8> {
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header(width, height));
+    chunk(&mut out, b"IDAT", &compress(width, height, pixels));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+// An animated PNG, built up a frame at a time. Each frame is compressed as it's added, so a long
+// animation doesn't hold on to its pixels.
+pub struct Animation {
+    width: u32,
+    height: u32,
+    frames: Vec<Vec<u8>>,
+}
+
+impl Animation {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            frames: Vec::new(),
+        }
+    }
+
+    // Adds a frame covering the whole image, given the same way as to `encode`
+    pub fn push(&mut self, pixels: &[[u8; 3]]) {
+        self.frames.push(compress(self.width, self.height, pixels));
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    // The animation, showing each frame for `delay` milliseconds and the last for `hold`, played
+    // on a loop. Viewers without animation support show the first frame.
+    pub fn finish(self, delay: u16, hold: u16) -> Vec<u8> {
+        assert!(!self.frames.is_empty(), "an animation needs a frame");
+
+        let mut out = SIGNATURE.to_vec();
+        chunk(&mut out, b"IHDR", &header(self.width, self.height));
+        let mut control = (self.frames.len() as u32).to_be_bytes().to_vec();
+        // Zero plays means forever
+        control.extend(0u32.to_be_bytes());
+        chunk(&mut out, b"acTL", &control);
+
+        // Frame controls and the frames after the first share one sequence of numbers
+        let mut sequence = 0u32;
+        let count = self.frames.len();
+        for (index, data) in self.frames.into_iter().enumerate() {
+            let mut frame = sequence.to_be_bytes().to_vec();
+            sequence += 1;
+            frame.extend(self.width.to_be_bytes());
+            frame.extend(self.height.to_be_bytes());
+            // At the top left corner
+            frame.extend([0; 8]);
+            let delay = if index + 1 == count { hold } else { delay };
+            frame.extend(delay.to_be_bytes());
+            frame.extend(1000u16.to_be_bytes());
+            // Nothing is cleared or blended, since every frame covers the whole image
+            frame.extend([0, 0]);
+            chunk(&mut out, b"fcTL", &frame);
+
+            if index == 0 {
+                chunk(&mut out, b"IDAT", &data);
+            } else {
+                let mut body = sequence.to_be_bytes().to_vec();
+                sequence += 1;
+                body.extend(data);
+                chunk(&mut out, b"fdAT", &body);
+            }
+        }
+
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn header(width: u32, height: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel of RGB, with the standard compression and filtering and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+    header
+}
+
+// The pixels as the zlib stream an image's data chunks hold
+fn compress(width: u32, height: u32, pixels: &[[u8; 3]]) -> Vec<u8> {
+    assert_eq!(pixels.len(), width as usize * height as usize);
+
+    // Every row starts with the filter type, which is always none
+    let mut raw = Vec::with_capacity(pixels.len() * 3 + height as usize);
+    for row in pixels.chunks(width.max(1) as usize) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+    zlib(&raw, 1 + width as usize * 3)
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// A zlib stream holding `data` as a single deflate block. Matches are only looked for one pixel
+// back and one row of `stride` bytes back.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = Bits::default();
+    // The last block, compressed with the fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let distances = [3, stride];
+    let mut at = 0;
+    while at < data.len() {
+        let best = distances
+            .into_iter()
+            .filter(|&distance| distance <= at && distance <= MAX_DISTANCE)
+            .map(|distance| {
+                let length = (0..MAX_MATCH.min(data.len() - at))
+                    .take_while(|&offset| data[at + offset] == data[at + offset - distance])
+                    .count();
+                (length, distance)
+            })
+            .max();
+
+        match best {
+            Some((length, distance)) if length >= MIN_MATCH => {
+                bits.length(length);
+                bits.distance(distance);
+                at += length;
+            }
+            _ => {
+                bits.literal(data[at] as u16);
+                at += 1;
+            }
+        }
+    }
+    bits.literal(256);
+
+    let mut out = vec![0x78, 0x01];
+    out.extend(bits.finish());
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+// Deflate's bit stream, which packs values from the lowest bit up but Huffman codes from their
+// highest bit
+#[derive(Default)]
+struct Bits {
+    out: Vec<u8>,
+    pending: u32,
+    count: u32,
+}
+
+impl Bits {
+    fn write(&mut self, value: u32, count: u32) {
+        self.pending |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.pending as u8);
+            self.pending >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    // A literal byte, or one of the length codes from 256 on
+    fn literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn length(&mut self, length: usize) {
+        let index = LENGTHS
+            .iter()
+            .rposition(|&(base, _)| base as usize <= length)
+            .expect("matches are at least 3 long");
+        let (base, extra) = LENGTHS[index];
+        self.literal(257 + index as u16);
+        self.write((length - base as usize) as u32, extra as u32);
+    }
+
+    fn distance(&mut self, distance: usize) {
+        let index = DISTANCES
+            .iter()
+            .rposition(|&(base, _)| base as usize <= distance)
+            .expect("distances are at least 1");
+        let (base, extra) = DISTANCES[index];
+        self.code(index as u32, 5);
+        self.write((distance - base as usize) as u32, extra as u32);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.pending as u8);
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Reads deflate's bit stream, the opposite of `Bits`
+    struct Reader<'a> {
+        bytes: &'a [u8],
+        at: usize,
+    }
+
+    impl Reader<'_> {
+        fn bit(&mut self) -> u32 {
+            let bit = (self.bytes[self.at / 8] >> (self.at % 8)) & 1;
+            self.at += 1;
+            bit as u32
+        }
+
+        fn value(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |value, shift| value | self.bit() << shift)
+        }
+
+        fn code(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |code, _| code << 1 | self.bit())
+        }
+
+        // A literal byte or length code, from the fixed codes
+        fn literal(&mut self) -> u32 {
+            let code = self.code(7);
+            if code < 0x18 {
+                return 256 + code;
+            }
+            let code = code << 1 | self.bit();
+            match code {
+                0x30..=0xbf => code - 0x30,
+                0xc0..=0xc7 => 280 + code - 0xc0,
+                _ => 144 + (code << 1 | self.bit()) - 0x190,
+            }
+        }
+    }
+
+    // Undoes `zlib`, which only ever writes one block with the fixed codes
+    fn inflate(stream: &[u8]) -> Vec<u8> {
+        assert_eq!(stream[..2], [0x78, 0x01]);
+        let mut reader = Reader {
+            bytes: &stream[2..stream.len() - 4],
+            at: 0,
+        };
+        assert_eq!(
+            reader.value(3),
+            0b011,
+            "one final block with the fixed codes"
+        );
+
+        let mut out = Vec::new();
+        loop {
+            let symbol = reader.literal();
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let (base, extra) = LENGTHS[symbol as usize - 257];
+                    let length = base as usize + reader.value(extra as u32) as usize;
+                    let (base, extra) = DISTANCES[reader.code(5) as usize];
+                    let distance = base as usize + reader.value(extra as u32) as usize;
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+        }
+
+        let checksum = u32::from_be_bytes(stream[stream.len() - 4..].try_into().unwrap());
+        assert_eq!(adler32(&out), checksum);
+        out
+    }
+
+    // The width, height and pixels of an image from `encode`, checking each chunk's CRC
+    pub(crate) fn decode(png: &[u8]) -> (u32, u32, Vec<[u8; 3]>) {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut rest = &png[8..];
+        let (mut width, mut height, mut data) = (0, 0, Vec::new());
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, body) = (&rest[4..8], &rest[8..8 + length]);
+            let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+            assert_eq!(crc32(&rest[4..8 + length]), crc);
+            match kind {
+                b"IHDR" => {
+                    width = u32::from_be_bytes(body[..4].try_into().unwrap());
+                    height = u32::from_be_bytes(body[4..8].try_into().unwrap());
+                    assert_eq!(body[8..], [8, 2, 0, 0, 0]);
+                }
+                b"IDAT" => data.extend(body),
+                _ => (),
+            }
+            rest = &rest[12 + length..];
+        }
+
+        let raw = inflate(&data);
+        let stride = 1 + width as usize * 3;
+        assert_eq!(raw.len(), stride * height as usize);
+        let pixels = raw
+            .chunks(stride)
+            .flat_map(|row| {
+                assert_eq!(row[0], 0, "rows aren't filtered");
+                row[1..]
+                    .chunks(3)
+                    .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            })
+            .collect();
+        (width, height, pixels)
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let (width, height) = (23, 7);
+        let pixels = (0..width * height)
+            .map(|at| match at % 5 {
+                0 | 1 => [0xff; 3],
+                2 => [at as u8, 0, 0],
+                _ => [0x12, 0x34, 0x56],
+            })
+            .collect::<Vec<_>>();
+
+        let png = encode(width, height, &pixels);
+        assert_eq!(decode(&png), (width, height, pixels));
+    }
+}
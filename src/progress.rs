@@ -0,0 +1,66 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// How often the display is redrawn at most, so that fast batches don't flood the terminal
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// A line on stderr showing how many of a batch's dates are done, redrawn in place as they
+// complete. It does nothing when stderr isn't a terminal, so piped output stays clean.
+pub struct Progress {
+    total: usize,
+    started: Instant,
+    enabled: bool,
+    // The number of completed items and when the line was last drawn
+    state: Mutex<(usize, Option<Instant>)>,
+}
+
+impl Progress {
+    pub fn new(total: usize, quiet: bool) -> Self {
+        Self {
+            total,
+            started: Instant::now(),
+            enabled: !quiet && io::stderr().is_terminal(),
+            state: Mutex::new((0, None)),
+        }
+    }
+
+    // Records that another item finished. Safe to call from any thread.
+    pub fn tick(&self) {
+        let mut state = self.state.lock().unwrap();
+        let (done, last_drawn) = &mut *state;
+        *done += 1;
+
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        if last_drawn.is_some_and(|last| now - last < REDRAW_INTERVAL) && *done < self.total {
+            return;
+        }
+        *last_drawn = Some(now);
+
+        let elapsed = now - self.started;
+        let remaining = elapsed.mul_f64((self.total - *done) as f64 / *done as f64);
+        eprint!(
+            "\r\x1b[K{done}/{} dates, {:.1}s elapsed, about {:.0}s left",
+            self.total,
+            elapsed.as_secs_f64(),
+            remaining.as_secs_f64()
+        );
+        io::stderr().flush().ok();
+    }
+}
+
+impl Drop for Progress {
+    // Clears the line so that whatever is printed next starts on a clean line
+    fn drop(&mut self) {
+        let (_, last_drawn) = *self.state.lock().unwrap();
+        if last_drawn.is_some() {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
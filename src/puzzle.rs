@@ -0,0 +1,679 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::ControlFlow,
+    sync::Arc,
+};
+
+use crate::{
+    backtrack::{
+        self, CancelToken, Interrupted, Limits, Order, ProgressHook, SearchProgress, SearchStats,
+        Stats,
+    },
+    bitboard::BitBoard,
+    entity::{EncodeError, EncodingBoard, Point, PuzzleError, Tile, Transforms},
+    variant::Variant,
+};
+
+// A board along with the tiles which have to cover it, and every placement of each tile on the
+// board. Built with `PuzzleBuilder`, which checks that the pieces fit together.
+#[derive(Clone)]
+pub struct Puzzle<B = u64> {
+    board: EncodingBoard<B>,
+    tiles: Vec<Tile>,
+    transforms: Transforms,
+    configurations: Vec<BTreeSet<B>>,
+}
+
+impl Puzzle {
+    pub fn from_variant(variant: &Variant) -> Result<Self, PuzzleError> {
+        Self::builder()
+            .board((variant.board)())
+            .tiles((variant.tiles)())
+            .build()
+    }
+}
+
+impl<B: BitBoard> Puzzle<B> {
+    pub fn builder() -> PuzzleBuilder<B> {
+        PuzzleBuilder::default()
+    }
+
+    pub fn board(&self) -> &EncodingBoard<B> {
+        &self.board
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn transforms(&self) -> Transforms {
+        self.transforms
+    }
+
+    // Every placement of each tile on the board, indexed by tile
+    pub fn configurations(&self) -> &[BTreeSet<B>] {
+        &self.configurations
+    }
+
+    pub fn solver(&self) -> Solver<B> {
+        Solver::new(self)
+    }
+}
+
+pub struct PuzzleBuilder<B = u64> {
+    board: Option<EncodingBoard<B>>,
+    tiles: Vec<Tile>,
+    transforms: Transforms,
+    goal_cells: Option<Vec<Point>>,
+}
+
+impl<B> Default for PuzzleBuilder<B> {
+    fn default() -> Self {
+        Self {
+            board: None,
+            tiles: Vec::new(),
+            transforms: Transforms::RotationsAndReflections,
+            goal_cells: None,
+        }
+    }
+}
+
+impl<B: BitBoard> PuzzleBuilder<B> {
+    pub fn board(mut self, board: EncodingBoard<B>) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    pub fn add_tile(mut self, tile: Tile) -> Self {
+        self.tiles.push(tile);
+        self
+    }
+
+    pub fn tiles(mut self, tiles: impl IntoIterator<Item = Tile>) -> Self {
+        self.tiles.extend(tiles);
+        self
+    }
+
+    // Whether tiles may be flipped over as well as rotated, which they can be by default
+    pub fn allow_reflections(mut self, allow: bool) -> Self {
+        self.transforms = if allow {
+            Transforms::RotationsAndReflections
+        } else {
+            Transforms::Rotations
+        };
+        self
+    }
+
+    // The orientations tiles may be placed in, for puzzles which need something other than
+    // `allow_reflections` gives
+    pub fn transforms(mut self, transforms: Transforms) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    // Cells typical of the ones left uncovered by a solution, such as a date's. They're only
+    // used to check that the tiles can fit around them.
+    pub fn goal_cells(mut self, goal_cells: &[Point]) -> Self {
+        self.goal_cells = Some(goal_cells.to_vec());
+        self
+    }
+
+    // Checks that the tiles can fit on the board, and works out every placement of each of them
+    pub fn build(self) -> Result<Puzzle<B>, PuzzleError> {
+        let board = self.board.ok_or(PuzzleError::MissingBoard)?;
+
+        if let Some(goal_cells) = &self.goal_cells {
+            board.encode_checked(goal_cells.iter().copied()).map_err(
+                |EncodeError::NotOnBoard { point }| PuzzleError::GoalOutsideBoard { point },
+            )?;
+        }
+
+        // The tiles have to fit in the cells the goal leaves free, or in the whole board without
+        // one. Fewer cells are fine, since then some cells are left uncovered as well.
+        let area = self.tiles.iter().map(|tile| tile.area()).sum();
+        let goal = self.goal_cells.as_ref().map_or(0, Vec::len);
+        let free = board.points().count().saturating_sub(goal);
+        if area > free {
+            return Err(PuzzleError::TilesTooLarge { area, free });
+        }
+
+        let configurations = self
+            .tiles
+            .iter()
+            .map(|tile| tile_configurations(&board, tile, self.transforms))
+            .collect();
+
+        Ok(Puzzle {
+            board,
+            tiles: self.tiles,
+            transforms: self.transforms,
+            configurations,
+        })
+    }
+}
+
+// Every placement of the tile on the board, under each orientation allowed by `transforms`
+fn tile_configurations<B: BitBoard>(
+    encoding_board: &EncodingBoard<B>,
+    tile: &Tile,
+    transforms: Transforms,
+) -> BTreeSet<B> {
+    let mut tile_configs = BTreeSet::new();
+    let bounds = encoding_board.bounds();
+
+    for tile in tile.orientations(transforms) {
+        // Offsets which take the tile past the edges of the board can't be placements, and
+        // checking the bounding box is much cheaper than encoding every point
+        let tile_bounds = tile.bounding_box();
+        for offset in encoding_board.points() {
+            if !tile_bounds.fits_within(offset, &bounds) {
+                continue;
+            }
+            if let Some(enc) = encoding_board.encode(tile.offset_points(offset)) {
+                tile_configs.insert(enc);
+            }
+        }
+    }
+
+    tile_configs
+}
+
+// Flattens the configurations of each tile into a single placement list, dropping any placements
+// which collide with the initial board. Placements for tile `i` occupy the range
+// `placement_indices[i]..placement_indices[i + 1]`.
+pub fn filter_placements<B: BitBoard>(
+    configurations: &[BTreeSet<B>],
+    initial_board: B,
+) -> (Vec<B>, Vec<usize>) {
+    let mut placements = Vec::new();
+    let mut placement_indices = Vec::with_capacity(configurations.len() + 1);
+
+    for configs in configurations {
+        placement_indices.push(placements.len());
+
+        for &enc in configs
+            .iter()
+            .filter(|&&enc| enc.is_disjoint(initial_board))
+        {
+            placements.push(enc);
+        }
+    }
+
+    placement_indices.push(placements.len());
+
+    (placements, placement_indices)
+}
+
+// Why a search couldn't say whether there's a solution
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SolveError {
+    // One of the cells to leave uncovered isn't on the board
+    Goal(EncodeError),
+    Interrupted(Interrupted),
+}
+
+impl From<Interrupted> for SolveError {
+    fn from(interrupted: Interrupted) -> Self {
+        SolveError::Interrupted(interrupted)
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Goal(error) => write!(f, "{error}"),
+            SolveError::Interrupted(Interrupted::TimedOut) => write!(f, "the search timed out"),
+            SolveError::Interrupted(Interrupted::Cancelled) => {
+                write!(f, "the search was cancelled")
+            }
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+// Solves a puzzle for any set of cells to leave uncovered. Every placement of every tile is
+// worked out once up front, so one solver can answer many dates quickly.
+pub struct Solver<B = u64> {
+    board: EncodingBoard<B>,
+    configurations: Vec<BTreeSet<B>>,
+    order: Order,
+    cancel: Option<CancelToken>,
+}
+
+impl<B: BitBoard> Solver<B> {
+    pub fn new(puzzle: &Puzzle<B>) -> Self {
+        Self {
+            board: puzzle.board.clone(),
+            configurations: puzzle.configurations.clone(),
+            order: Order::Static,
+            cancel: None,
+        }
+    }
+
+    pub fn with_order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    // Makes searches give up with `Interrupted::Cancelled` soon after the token is cancelled
+    pub fn with_cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    // The first solution leaving exactly `goal_cells` uncovered, or `None` if there isn't one. No
+    // goal cells means covering the whole board. Fails if some of the cells aren't on the board
+    // or the search was cancelled.
+    pub fn solve(&self, goal_cells: &[Point]) -> Result<Option<Solution<B>>, SolveError> {
+        self.solve_tracked(goal_cells, |_| ()).0
+    }
+
+    // Like `solve`, along with counters for how much searching it took
+    pub fn solve_with_stats(
+        &self,
+        goal_cells: &[Point],
+    ) -> (Result<Option<Solution<B>>, SolveError>, SearchStats) {
+        self.solve_tracked(goal_cells, |_| SearchStats::default())
+    }
+
+    // Like `solve`, calling `callback` every `interval` placements with how far the search has
+    // got. The callback can stop the search by returning `ControlFlow::Break`, in which case
+    // `Interrupted::Cancelled` is returned.
+    pub fn solve_with_progress(
+        &self,
+        goal_cells: &[Point],
+        interval: u64,
+        callback: impl FnMut(&SearchProgress) -> ControlFlow<()>,
+    ) -> Result<Option<Solution<B>>, SolveError> {
+        let (solution, _) = self.solve_tracked(goal_cells, |placement_indices| {
+            ProgressHook::new(placement_indices.to_vec(), interval, callback)
+        });
+        solution
+    }
+
+    // The stats are made once the placements for the goal are known, from their indices
+    fn solve_tracked<S: Stats>(
+        &self,
+        goal_cells: &[Point],
+        stats: impl FnOnce(&[usize]) -> S,
+    ) -> (Result<Option<Solution<B>>, SolveError>, S) {
+        let initial_board = match self.board.encode_checked(goal_cells.iter().copied()) {
+            Ok(initial_board) => initial_board,
+            // The search never starts, so the stats have no pieces to count
+            Err(error) => return (Err(SolveError::Goal(error)), stats(&[0])),
+        };
+        let (placements, placement_indices) =
+            filter_placements(&self.configurations, initial_board);
+        let mut stats = stats(&placement_indices);
+
+        let solution = backtrack::backtrack(
+            initial_board,
+            &placements,
+            &placement_indices,
+            self.order,
+            &Limits {
+                deadline: None,
+                cancel: self.cancel.clone(),
+            },
+            &mut stats,
+        );
+
+        let solution = solution.map_err(SolveError::from).map(|solution| {
+            solution.map(|solution| {
+                Solution::new(
+                    &self.board,
+                    solution.iter().map(|&index| placements[index]).collect(),
+                    initial_board,
+                )
+            })
+        });
+        (solution, stats)
+    }
+
+    // Every solution leaving exactly `goal_cells` uncovered, found one at a time as the iterator is
+    // advanced. There are none if some of the cells aren't on the board.
+    pub fn solutions(&self, goal_cells: &[Point]) -> SolutionIter<'_, B> {
+        SolutionIter::new(&self.board, &self.configurations, goal_cells)
+    }
+}
+
+// The backtracking search with its state kept between calls to `next`, so that it picks up where
+// the last solution was found. Pieces are always placed in order, whatever the solver's order
+// is, since that's the search whose state is a plain stack.
+pub struct SolutionIter<'a, B = u64> {
+    board: &'a EncodingBoard<B>,
+    goal: B,
+    placements: Vec<B>,
+    placement_indices: Vec<usize>,
+    // The next placement index to try for each piece
+    selected_placements: Vec<usize>,
+    // The board each piece was placed on, to go back to when moving it again
+    board_states: Vec<B>,
+    // The piece being placed, and the board with every piece before it
+    piece: usize,
+    current: B,
+    done: bool,
+}
+
+impl<'a, B: BitBoard> SolutionIter<'a, B> {
+    fn new(
+        board: &'a EncodingBoard<B>,
+        configurations: &[BTreeSet<B>],
+        goal_cells: &[Point],
+    ) -> Self {
+        // Goal cells off the board leave nothing to search, while no goal cells means covering
+        // the whole board
+        let goal = board.encode_checked(goal_cells.iter().copied());
+        let done = goal.is_err();
+        let goal = goal.unwrap_or(B::EMPTY);
+        let (placements, placement_indices) = filter_placements(configurations, goal);
+        let pieces = placement_indices.len() - 1;
+
+        Self {
+            board,
+            goal,
+            selected_placements: placement_indices[..pieces].to_vec(),
+            board_states: vec![B::EMPTY; pieces],
+            placements,
+            placement_indices,
+            piece: 0,
+            current: goal,
+            done,
+        }
+    }
+
+    fn solution(&self) -> Solution<B> {
+        let tiles = self
+            .selected_placements
+            .iter()
+            .map(|&selected| self.placements[selected - 1])
+            .collect();
+        Solution::new(self.board, tiles, self.goal)
+    }
+}
+
+impl<B: BitBoard> Iterator for SolutionIter<'_, B> {
+    type Item = Solution<B>;
+
+    fn next(&mut self) -> Option<Solution<B>> {
+        if self.done {
+            return None;
+        }
+
+        let pieces = self.selected_placements.len();
+        if pieces == 0 {
+            self.done = true;
+            return Some(Solution::new(self.board, Vec::new(), self.goal));
+        }
+
+        loop {
+            let i = self.piece;
+            let bound = self.placement_indices[i + 1];
+            let next = (self.selected_placements[i]..bound)
+                .find(|&index| self.placements[index].is_disjoint(self.current));
+
+            let Some(index) = next else {
+                // Every placement of this piece has been tried, so start it over and move the
+                // piece before it instead
+                self.selected_placements[i] = self.placement_indices[i];
+                if i == 0 {
+                    self.done = true;
+                    return None;
+                }
+                self.piece -= 1;
+                self.current = self.board_states[self.piece];
+                continue;
+            };
+
+            self.selected_placements[i] = index + 1;
+            self.board_states[i] = self.current;
+
+            // The last piece fits, so this is a solution. The next call tries the piece's
+            // remaining placements on the same board.
+            if i + 1 == pieces {
+                return Some(self.solution());
+            }
+
+            self.current |= self.placements[index];
+            self.piece += 1;
+        }
+    }
+}
+
+// Identifies a tile by its index in the puzzle's tiles
+pub type TileId = usize;
+
+// Where each tile went in a solution, as an encoding of its cells on the board, along with the
+// cells which were meant to be left uncovered
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Solution<B = u64> {
+    // The point each bit of an encoding stands for, as in the board
+    cells: Arc<[Point]>,
+    tiles: Vec<B>,
+    goal: B,
+}
+
+impl<B: BitBoard> Solution<B> {
+    pub fn new(board: &EncodingBoard<B>, tiles: Vec<B>, goal: B) -> Self {
+        Self {
+            cells: board.shared_cells().clone(),
+            tiles,
+            goal,
+        }
+    }
+
+    // The encoding of each tile's cells, indexed by tile
+    pub fn tiles(&self) -> &[B] {
+        &self.tiles
+    }
+
+    // The encoding of the cells meant to be left uncovered, such as the date's
+    pub fn goal(&self) -> B {
+        self.goal
+    }
+
+    pub fn tile_at(&self, point: Point) -> Option<TileId> {
+        let bit = self.cells.iter().position(|&cell| cell == point)?;
+        self.tiles.iter().position(|&enc| enc.contains(bit as u32))
+    }
+
+    // The tile's cells, in bit order. Tiles which aren't in the solution have none.
+    pub fn cells_of(&self, id: TileId) -> impl Iterator<Item = Point> + '_ {
+        self.points(self.tiles.get(id).copied().unwrap_or(B::EMPTY))
+    }
+
+    pub fn goal_cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points(self.goal)
+    }
+
+    // Every cell no tile covers, including the goal cells
+    pub fn uncovered_cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points(!self.tiles.iter().fold(B::EMPTY, |board, &enc| board | enc))
+    }
+
+    // The cells no tile covers besides the goal cells, which only happens with fewer tiles than
+    // the board needs
+    pub fn extra_cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points(!self.tiles.iter().fold(self.goal, |board, &enc| board | enc))
+    }
+
+    fn points(&self, enc: B) -> impl Iterator<Item = Point> + '_ {
+        enc.ones()
+            .map_while(|bit| self.cells.get(bit as usize).copied())
+    }
+}
+
+// Keeps the first of each group of solutions which are turned or flipped copies of each other on
+// the board, as found by `EncodingBoard::symmetries`. The solutions have to be on the board.
+pub fn distinct_solutions<B: BitBoard>(
+    board: &EncodingBoard<B>,
+    solutions: impl IntoIterator<Item = Solution<B>>,
+) -> Vec<Solution<B>> {
+    let symmetries = board.symmetries();
+    let mut seen = HashSet::new();
+
+    solutions
+        .into_iter()
+        .filter(|solution| {
+            // The smallest of the solution's copies stands for all of them
+            let canonical = symmetries
+                .iter()
+                .filter_map(|&transform| {
+                    let goal = board.transform_encoding(transform, solution.goal())?;
+                    let tiles = solution
+                        .tiles()
+                        .iter()
+                        .map(|&enc| board.transform_encoding(transform, enc))
+                        .collect::<Option<Vec<_>>>()?;
+                    Some((goal, tiles))
+                })
+                .min();
+            seen.insert(canonical)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{
+        date::{Date, Month},
+        variant,
+    };
+
+    fn classic() -> Puzzle {
+        Puzzle::from_variant(variant::classic()).unwrap()
+    }
+
+    // Leaving these two cells uncovered cuts the top left corner off from the rest of the board,
+    // which the search takes a long time to find out
+    const HOPELESS: [Point; 2] = [Point { x: 1, y: 6 }, Point { x: 0, y: 5 }];
+
+    #[test]
+    fn cancelling_a_hopeless_search_stops_it_promptly() {
+        let puzzle = classic();
+        let cancel = CancelToken::new();
+        let solver = puzzle.solver().with_cancel_token(cancel.clone());
+
+        let started = Instant::now();
+        let result = thread::scope(|scope| {
+            let search = scope.spawn(|| solver.solve(&HOPELESS));
+            thread::sleep(Duration::from_millis(50));
+            cancel.cancel();
+            search.join().unwrap()
+        });
+
+        assert_eq!(result, Err(SolveError::Interrupted(Interrupted::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    // The classic board moved off the origin, labels and all, solves the same way
+    #[test]
+    fn offset_boards_solve_like_the_original() {
+        let puzzle = classic();
+        let offset = Point { x: 5, y: -3 };
+        let mut board =
+            EncodingBoard::from_points(puzzle.board().points().map(|point| point + offset))
+                .unwrap();
+        for (point, label) in puzzle.board().labels() {
+            board.set_label(point + offset, label.clone()).unwrap();
+        }
+        let shifted = Puzzle::builder()
+            .board(board.clone())
+            .tiles(puzzle.tiles().to_vec())
+            .build()
+            .unwrap();
+
+        let (normalized, applied) = board.normalized();
+        assert_eq!(applied, -offset);
+        assert!(normalized == *puzzle.board());
+
+        let dates = [(Month::January, 1), (Month::October, 13)];
+        for (month, day) in dates {
+            let date = Date {
+                year: None,
+                month,
+                day,
+            };
+            let goal = shifted.board().date_points(date);
+            assert_eq!(
+                goal,
+                puzzle
+                    .board()
+                    .date_points(date)
+                    .into_iter()
+                    .map(|point| point + offset)
+                    .collect::<Vec<_>>()
+            );
+
+            let expected = puzzle
+                .solver()
+                .solve(&goal.iter().map(|&point| point - offset).collect::<Vec<_>>());
+            let expected = expected.unwrap().unwrap();
+            let solution = shifted.solver().solve(&goal).unwrap().unwrap();
+            for id in 0..puzzle.tiles().len() {
+                assert!(
+                    solution
+                        .cells_of(id)
+                        .eq(expected.cells_of(id).map(|point| point + offset)),
+                    "{date}"
+                );
+            }
+        }
+    }
+
+    // A board of two cells covered by a single domino
+    fn domino() -> Puzzle {
+        let cells = [Point { x: 0, y: 0 }, Point { x: 1, y: 0 }];
+        Puzzle::builder()
+            .board(EncodingBoard::from_points(cells).unwrap())
+            .tiles(vec![Tile::new(cells.to_vec()).unwrap()])
+            .build()
+            .unwrap()
+    }
+
+    // No goal cells means the tiles have to cover the whole board
+    #[test]
+    fn full_covers_solve_with_no_goal() {
+        let puzzle = domino();
+        let solution = puzzle.solver().solve(&[]).unwrap().unwrap();
+        assert_eq!(solution.goal(), 0);
+        assert_eq!(solution.cells_of(0).count(), 2);
+        assert_eq!(puzzle.solver().solutions(&[]).count(), 1);
+    }
+
+    #[test]
+    fn goals_off_the_board_are_errors() {
+        let puzzle = domino();
+        let point = Point { x: 2, y: 0 };
+        assert_eq!(
+            puzzle
+                .solver()
+                .solve(&[point])
+                .map(|solution| solution.is_some()),
+            Err(SolveError::Goal(EncodeError::NotOnBoard { point }))
+        );
+        assert_eq!(puzzle.solver().solutions(&[point]).count(), 0);
+    }
+
+    #[test]
+    fn tiles_larger_than_the_board_are_rejected() {
+        let cell = Point { x: 0, y: 0 };
+        let result = Puzzle::<u64>::builder()
+            .board(EncodingBoard::from_points([cell]).unwrap())
+            .tiles(vec![Tile::new(vec![cell, Point { x: 1, y: 0 }]).unwrap()])
+            .build();
+        assert!(matches!(
+            result,
+            Err(PuzzleError::TilesTooLarge { area: 2, free: 1 })
+        ));
+    }
+}
@@ -0,0 +1,1907 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter, Write},
+    io,
+};
+
+use crate::{
+    bitboard::BitBoard,
+    entity::{DecodeError, DecodingBoard, Label, Point, Square, Tile},
+    puzzle::Solution,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ascii(Style),
+    Unicode(Style),
+    // One character per cell, in the form `verify` reads
+    Grid(Style),
+    Json,
+    Coords,
+    // A picture with each tile as one outlined shape, as for printing or cutting out
+    Svg,
+    // A table with a colored cell per square, in a page which needs nothing else to show it
+    Html,
+    // A Markdown table with a cell per square, for pasting into issues and notes
+    Markdown,
+    // The grid format in a Markdown code block, for where tables don't show well
+    MarkdownGrid,
+    // An image with the given number of pixels per cell
+    #[cfg(feature = "png")]
+    Png(u32),
+}
+
+// Pixels per cell in a PNG image, unless another scale is asked for
+#[cfg(feature = "png")]
+pub const DEFAULT_SCALE: u32 = 32;
+
+// How the text formats draw the squares of the board
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    // Paints each tile with its own background color using ANSI escapes
+    pub color: bool,
+    // Leaves covered cells empty instead of marking them with their tile's letter
+    pub blank: bool,
+    // Follows the board with each tile's letter over its shape, as drawn by `legend`
+    pub legend: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style {
+        color: false,
+        blank: false,
+        legend: false,
+    };
+}
+
+// Background colors for the tiles, given out by tile id so that a tile keeps its color from one
+// run to the next. Black and white are left out so the board's own cells stand out.
+const PALETTE: [u8; 12] = [41, 42, 43, 44, 45, 46, 101, 102, 103, 104, 105, 106];
+
+impl Format {
+    const NAMES: [(&'static str, Format); 10] = [
+        ("ascii", Format::Ascii(Style::PLAIN)),
+        ("unicode", Format::Unicode(Style::PLAIN)),
+        ("grid", Format::Grid(Style::PLAIN)),
+        ("compact", Format::Grid(Style::PLAIN)),
+        ("json", Format::Json),
+        ("svg", Format::Svg),
+        ("html", Format::Html),
+        ("markdown", Format::Markdown),
+        ("markdown-grid", Format::MarkdownGrid),
+        ("coords", Format::Coords),
+    ];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.eq_ignore_ascii_case("png") {
+            #[cfg(feature = "png")]
+            return Ok(Format::Png(DEFAULT_SCALE));
+            #[cfg(not(feature = "png"))]
+            return Err(
+                "the `png` format isn't compiled in, rebuild with `--features png` to use it"
+                    .to_owned(),
+            );
+        }
+
+        Self::NAMES
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, format)| format)
+            .ok_or_else(|| {
+                let names = Self::NAMES.map(|(name, _)| name);
+                format!(
+                    "unknown format `{s}`, expected one of: {}, png",
+                    names.join(", ")
+                )
+            })
+    }
+
+    // Whether the output is meant to be read by people, and can be surrounded by headers
+    pub fn is_text(&self) -> bool {
+        match self {
+            Format::Ascii(..) | Format::Unicode(..) | Format::Grid(..) => true,
+            Format::Json
+            | Format::Coords
+            | Format::Svg
+            | Format::Html
+            | Format::Markdown
+            | Format::MarkdownGrid => false,
+            #[cfg(feature = "png")]
+            Format::Png(..) => false,
+        }
+    }
+
+    // The same format drawn at another number of pixels per cell, which only images have
+    #[cfg_attr(not(feature = "png"), allow(unused_variables))]
+    pub fn with_scale(self, scale: u32) -> Option<Self> {
+        match self {
+            #[cfg(feature = "png")]
+            Format::Png(..) => Some(Format::Png(scale)),
+            _ => None,
+        }
+    }
+
+    // What goes before everything the format writes, which only HTML needs
+    pub fn prelude(&self) -> &'static str {
+        match self {
+            Format::Html => HTML_PRELUDE,
+            _ => "",
+        }
+    }
+
+    // What goes after everything the format writes, closing what `prelude` opened
+    pub fn epilogue(&self) -> &'static str {
+        match self {
+            Format::Html => "</body>\n</html>\n",
+            _ => "",
+        }
+    }
+
+    // A heading to go above a solution, for the formats which can have one
+    pub fn heading(&self, text: &str) -> Option<String> {
+        match self {
+            Format::Html => Some(format!("<h2>{}</h2>\n", xml_escape(text))),
+            Format::Markdown | Format::MarkdownGrid => {
+                Some(format!("### {}\n\n", markdown_escape(text)))
+            }
+            _ if self.is_text() => Some(format!("{text}\n")),
+            _ => None,
+        }
+    }
+
+    // The file extension used when writing the output to files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Svg => "svg",
+            Format::Html => "html",
+            Format::Markdown | Format::MarkdownGrid => "md",
+            #[cfg(feature = "png")]
+            Format::Png(..) => "png",
+            Format::Ascii(..) | Format::Unicode(..) | Format::Grid(..) | Format::Coords => "txt",
+        }
+    }
+
+    // The same format drawn in the given style, which only the text formats have
+    pub fn with_style(mut self, new_style: Style) -> Self {
+        if let Format::Ascii(style) | Format::Unicode(style) | Format::Grid(style) = &mut self {
+            *style = new_style;
+        }
+        self
+    }
+
+    // The key to go under a drawing of the board, for the text formats drawn with one
+    pub fn legend(&self, board: &DecodingBoard, tiles: &[Tile]) -> Option<String> {
+        let (Format::Ascii(style) | Format::Unicode(style) | Format::Grid(style)) = *self else {
+            return None;
+        };
+        if !style.legend {
+            return None;
+        }
+
+        // The key wraps at the drawing's width, though a narrow board still gets a few tiles a row
+        let columns = layout(board).first().map_or(0, Vec::len);
+        let width = match self {
+            Format::Grid(..) => columns,
+            _ => columns * 4 + 1,
+        };
+        Some(legend(tiles, style, width.max(LEGEND_WIDTH)))
+    }
+
+    // Draws the board as it is. JSON, coordinates and SVG need a finished solution instead, and
+    // images aren't text, so those can't be drawn this way.
+    pub fn render(&self, board: &DecodingBoard) -> Result<String, RenderError> {
+        Ok(self.drawing(board)?.to_string())
+    }
+
+    // Like `render`, but written straight to `out`
+    pub fn render_to(&self, board: &DecodingBoard, out: &mut impl io::Write) -> io::Result<()> {
+        let drawing = self.drawing(board).map_err(io::Error::other)?;
+        write!(out, "{drawing}")
+    }
+
+    fn drawing<'a>(&self, board: &'a DecodingBoard) -> Result<Drawing<'a>, RenderError> {
+        match self {
+            Format::Json | Format::Coords | Format::Svg => Err(RenderError::NeedsSolution),
+            #[cfg(feature = "png")]
+            Format::Png(..) => Err(RenderError::NotText),
+            _ => Ok(Drawing {
+                format: *self,
+                board,
+            }),
+        }
+    }
+
+    // Draws the solution on `board` for the text formats, which any previous solution on it
+    // doesn't affect. Images aren't text, so they can only be written with `render_solution_to`.
+    pub fn render_solution<B: BitBoard>(
+        &self,
+        board: &mut DecodingBoard,
+        solution: &Solution<B>,
+    ) -> Result<String, RenderError> {
+        match self {
+            Format::Json => Ok(json(board, solution)),
+            Format::Svg => Ok(svg(board, solution)),
+            Format::Coords => Ok(coords(solution)),
+            Format::Ascii(..)
+            | Format::Unicode(..)
+            | Format::Grid(..)
+            | Format::Html
+            | Format::Markdown
+            | Format::MarkdownGrid => {
+                board.draw(solution)?;
+                self.render(board)
+            }
+            #[cfg(feature = "png")]
+            Format::Png(..) => Err(RenderError::NotText),
+        }
+    }
+
+    // Like `render_solution`, but written straight to `out`
+    pub fn render_solution_to<B: BitBoard>(
+        &self,
+        board: &mut DecodingBoard,
+        solution: &Solution<B>,
+        out: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let draw = |board: &mut DecodingBoard| {
+            board
+                .draw(solution)
+                .map_err(|error| io::Error::other(RenderError::from(error)))
+        };
+        match self {
+            Format::Json => write!(out, "{}", json(board, solution)),
+            Format::Svg => write!(out, "{}", svg(board, solution)),
+            Format::Coords => write!(out, "{}", coords(solution)),
+            Format::Ascii(..)
+            | Format::Unicode(..)
+            | Format::Grid(..)
+            | Format::Html
+            | Format::Markdown
+            | Format::MarkdownGrid => {
+                draw(board)?;
+                self.render_to(board, out)
+            }
+            #[cfg(feature = "png")]
+            &Format::Png(scale) => {
+                draw(board)?;
+                out.write_all(&png(board, scale))
+            }
+        }
+    }
+}
+
+// Why a board or solution couldn't be drawn in a format
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RenderError {
+    // JSON, coordinates and SVG are made from a solution, not just the squares of a board
+    NeedsSolution,
+    // Images can only be written as bytes
+    NotText,
+    // The solution doesn't fit on the board it was drawn on
+    Decode(DecodeError),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::NeedsSolution => write!(
+                f,
+                "JSON, coordinates and SVG can only be rendered from a solution"
+            ),
+            RenderError::NotText => write!(f, "images can't be rendered as a string"),
+            RenderError::Decode(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+impl From<DecodeError> for RenderError {
+    fn from(error: DecodeError) -> Self {
+        RenderError::Decode(error)
+    }
+}
+
+// A text drawing of the board, so that it can be written anywhere without building it up first
+struct Drawing<'a> {
+    format: Format,
+    board: &'a DecodingBoard,
+}
+
+impl Display for Drawing<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.format {
+            Format::Ascii(style) => ascii(f, self.board, style),
+            Format::Unicode(style) => unicode(f, self.board, style),
+            Format::Grid(style) => grid(f, self.board, style),
+            Format::Html => html(f, self.board),
+            Format::Markdown => markdown(f, self.board),
+            Format::MarkdownGrid => {
+                f.write_str("```text\n")?;
+                grid(f, self.board, Style::PLAIN)?;
+                f.write_str("```\n\n")
+            }
+            // `Format::drawing` only makes drawings of the formats above
+            Format::Json | Format::Coords | Format::Svg => Err(fmt::Error),
+            #[cfg(feature = "png")]
+            Format::Png(..) => Err(fmt::Error),
+        }
+    }
+}
+
+// The ASCII drawing, so that the board can be drawn into a string along with other output
+impl Display for DecodingBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        ascii(f, self, Style::PLAIN)
+    }
+}
+
+impl DecodingBoard {
+    // The ASCII drawing, written straight to `out`
+    pub fn render_to(&self, out: &mut impl io::Write) -> io::Result<()> {
+        Format::Ascii(Style::PLAIN).render_to(self, out)
+    }
+}
+
+// The squares laid out where they are on the board, top row first and with `None` off the board,
+// so that neighbors on the board are neighbors here too. Each comes with its label, if it has one.
+type Layout<'a> = Vec<Vec<Option<(Square, Option<&'a Label>)>>>;
+
+fn layout(board: &DecodingBoard) -> Layout<'_> {
+    let xs = || board.points().iter().flatten().map(|point| point.x);
+    let ys = || board.points().iter().flatten().map(|point| point.y);
+    let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) =
+        (xs().min(), xs().max(), ys().min(), ys().max())
+    else {
+        return Vec::new();
+    };
+
+    let width = (max_x - min_x + 1) as usize;
+    let mut layout = vec![vec![None; width]; (max_y - min_y + 1) as usize];
+    for ((point, square), label) in board.squares().zip(board.labels().iter().flatten()) {
+        layout[(max_y - point.y) as usize][(point.x - min_x) as usize] =
+            Some((square, label.as_ref()));
+    }
+    layout
+}
+
+// The square at the row and column of the layout, counting from one so that the squares just above
+// and left of it, which lie off the board, can be asked for too
+fn square_at(layout: &Layout, row: usize, col: usize) -> Option<Square> {
+    let row = layout.get(row.checked_sub(1)?)?;
+    let (square, _) = (*row.get(col.checked_sub(1)?)?)?;
+    Some(square)
+}
+
+// The middle of each square, padded to the width of a cell, along with a legend for the labels too
+// long to fit. Uncovered squares show their month or day, and `#` if they have no label.
+fn centers(layout: &Layout, style: Style) -> (Vec<Vec<String>>, Vec<String>) {
+    let mut legend = Vec::new();
+    let centers = layout
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&cell| {
+                    let center = match cell {
+                        None => " ".to_owned(),
+                        Some((Square::Covered { tile_id }, _)) if !style.blank => {
+                            tile_name(tile_id)
+                        }
+                        Some((Square::Covered { .. } | Square::Empty, _)) => " ".to_owned(),
+                        Some((Square::Uncovered, Some(label))) => {
+                            let text = label_text(label);
+                            if text.chars().count() <= 3 {
+                                text
+                            } else {
+                                legend.push(text);
+                                format!("#{}", legend.len())
+                            }
+                        }
+                        Some((Square::Uncovered, None)) => "#".to_owned(),
+                        Some((Square::Unknown, _)) => "?".to_owned(),
+                    };
+                    format!("{center:^3}")
+                })
+                .collect()
+        })
+        .collect();
+
+    let legend = legend
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| format!("#{}: {text}", index + 1))
+        .collect();
+    (centers, legend)
+}
+
+// How a label is printed on its cell, as on the physical board
+fn label_text(label: &Label) -> String {
+    match label {
+        Label::Month(month) => month.abbreviation().to_uppercase(),
+        Label::Day(day) => day.to_string(),
+        Label::Custom(text) => text.clone(),
+    }
+}
+
+fn write_legend(out: &mut impl Write, legend: &[String]) -> fmt::Result {
+    for line in legend {
+        writeln!(out, "{line}")?;
+    }
+    Ok(())
+}
+
+// `A` to `Z`, then `a` to `z`, then `0` to `9`, after which the tiles are numbered instead
+fn tile_name(tile_id: u8) -> String {
+    match tile_id {
+        0..26 => char::from(b'A' + tile_id).to_string(),
+        26..52 => char::from(b'a' + tile_id - 26).to_string(),
+        52..62 => char::from(b'0' + tile_id - 52).to_string(),
+        _ => tile_id.to_string(),
+    }
+}
+
+// Writes `text` over the background color of the square's tile when the style has colors, and
+// resets it straight after so that nothing carries over into the next cell or line
+fn paint(out: &mut impl Write, style: Style, square: Option<Square>, text: &str) -> fmt::Result {
+    match square {
+        Some(Square::Covered { tile_id }) if style.color => {
+            let color = PALETTE[tile_id as usize % PALETTE.len()];
+            write!(out, "\x1b[{color}m{text}\x1b[0m")
+        }
+        _ => out.write_str(text),
+    }
+}
+
+fn ascii(out: &mut impl Write, board: &DecodingBoard, style: Style) -> fmt::Result {
+    let layout = layout(board);
+    let (centers, legend) = centers(&layout, style);
+    let width = layout.first().map_or(0, Vec::len);
+
+    for vertex_row in 0..=layout.len() {
+        let mut line = String::new();
+
+        for vertex_col in 0..=width {
+            let up_left = square_at(&layout, vertex_row, vertex_col);
+            let up_right = square_at(&layout, vertex_row, vertex_col + 1);
+            let down_left = square_at(&layout, vertex_row + 1, vertex_col);
+            let down_right = square_at(&layout, vertex_row + 1, vertex_col + 1);
+
+            // Every corner of a cell is marked, whether or not any walls meet there
+            let corner = [up_left, up_right, down_left, down_right]
+                .iter()
+                .any(Option::is_some);
+            line.push(if corner { '+' } else { ' ' });
+            if vertex_col < width {
+                if up_right != down_right {
+                    line.push_str("---");
+                } else {
+                    paint(&mut line, style, up_right, "   ")?;
+                }
+            }
+        }
+        out.write_str(line.trim_end())?;
+        out.write_char('\n')?;
+
+        if vertex_row < layout.len() {
+            let line = cells_line(&layout, &centers, vertex_row, style, '|')?;
+            out.write_str(line.trim_end())?;
+            out.write_char('\n')?;
+        }
+    }
+
+    write_legend(out, &legend)
+}
+
+fn unicode(out: &mut impl Write, board: &DecodingBoard, style: Style) -> fmt::Result {
+    let layout = layout(board);
+    let (centers, legend) = centers(&layout, style);
+    let width = layout.first().map_or(0, Vec::len);
+
+    for vertex_row in 0..=layout.len() {
+        let mut line = String::new();
+
+        for vertex_col in 0..=width {
+            let up_left = square_at(&layout, vertex_row, vertex_col);
+            let up_right = square_at(&layout, vertex_row, vertex_col + 1);
+            let down_left = square_at(&layout, vertex_row + 1, vertex_col);
+            let down_right = square_at(&layout, vertex_row + 1, vertex_col + 1);
+
+            let up = up_left != up_right;
+            let down = down_left != down_right;
+            let left = up_left != down_left;
+            let right = up_right != down_right;
+
+            match junction(up, down, left, right) {
+                // With no edges meeting here, all four squares are the same
+                ' ' => paint(&mut line, style, up_left, " ")?,
+                ch => line.push(ch),
+            }
+            if vertex_col < width {
+                if right {
+                    line.push_str("───");
+                } else {
+                    paint(&mut line, style, up_right, "   ")?;
+                }
+            }
+        }
+        out.write_str(line.trim_end())?;
+        out.write_char('\n')?;
+
+        if vertex_row < layout.len() {
+            let line = cells_line(&layout, &centers, vertex_row, style, '│')?;
+            out.write_str(line.trim_end())?;
+            out.write_char('\n')?;
+        }
+    }
+
+    write_legend(out, &legend)
+}
+
+// The middles of a row's squares, with `wall` between squares which differ
+fn cells_line(
+    layout: &Layout,
+    centers: &[Vec<String>],
+    row: usize,
+    style: Style,
+    wall: char,
+) -> Result<String, fmt::Error> {
+    let mut line = String::new();
+    for col in 0..=centers[row].len() {
+        let left = square_at(layout, row + 1, col);
+        let square = square_at(layout, row + 1, col + 1);
+
+        if left != square {
+            line.push(wall);
+        } else {
+            paint(&mut line, style, square, " ")?;
+        }
+        if let Some(center) = centers[row].get(col) {
+            paint(&mut line, style, square, center)?;
+        }
+    }
+    Ok(line)
+}
+
+// Each cell as a single character at its place on the board, with spaces off the board. Covered
+// cells whose tile has no single character name, or any when the style is blank, are drawn as `*`.
+fn grid(out: &mut impl Write, board: &DecodingBoard, style: Style) -> fmt::Result {
+    for row in layout(board) {
+        let mut line = String::new();
+        for cell in row {
+            let Some((square, _)) = cell else {
+                line.push(' ');
+                continue;
+            };
+            let ch = match square {
+                Square::Covered { tile_id } if !style.blank => {
+                    Some(tile_name(tile_id)).filter(|name| name.len() == 1)
+                }
+                Square::Covered { .. } => None,
+                Square::Uncovered => Some("#".to_owned()),
+                Square::Empty => Some(".".to_owned()),
+                Square::Unknown => Some("?".to_owned()),
+            };
+            paint(&mut line, style, Some(square), ch.as_deref().unwrap_or("*"))?;
+        }
+        out.write_str(line.trim_end())?;
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+const HTML_PRELUDE: &str = "\
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Date puzzle</title>
+<style>
+table.board { border-collapse: collapse; margin-bottom: 1em; }
+table.board td { width: 2.5em; height: 2.5em; padding: 0; text-align: center; font-family: sans-serif; }
+</style>
+</head>
+<body>
+";
+
+// A table with a cell for each square, colored by its tile, and walls only between squares which
+// differ. Cells off the board are left empty.
+fn html(out: &mut impl Write, board: &DecodingBoard) -> fmt::Result {
+    let layout = layout(board);
+
+    out.write_str("<table class=\"board\">\n")?;
+    for (row_idx, row) in layout.iter().enumerate() {
+        out.write_str("<tr>")?;
+        for (col_idx, &cell) in row.iter().enumerate() {
+            let Some((square, label)) = cell else {
+                out.write_str("<td></td>")?;
+                continue;
+            };
+
+            let (fill, text) = match (square, label) {
+                (Square::Covered { tile_id }, _) => (FILLS[tile_id as usize % FILLS.len()], None),
+                (Square::Uncovered, Some(label)) => ("#fff", Some(label_text(label))),
+                (Square::Uncovered, None) => ("#fff", Some("#".to_owned())),
+                (Square::Empty, _) => ("#fff", None),
+                (Square::Unknown, _) => ("#fff", Some("?".to_owned())),
+            };
+            write!(out, "<td style=\"background: {fill};")?;
+            // `square_at` counts from one
+            let sides = [
+                ("top", row_idx, col_idx + 1),
+                ("right", row_idx + 1, col_idx + 2),
+                ("bottom", row_idx + 2, col_idx + 1),
+                ("left", row_idx + 1, col_idx),
+            ];
+            for (side, row, col) in sides {
+                if square_at(&layout, row, col) != Some(square) {
+                    write!(out, " border-{side}: 2px solid #000;")?;
+                }
+            }
+            write!(
+                out,
+                "\">{}</td>",
+                text.as_deref().map(xml_escape).unwrap_or_default()
+            )?;
+        }
+        out.write_str("</tr>\n")?;
+    }
+    out.write_str("</table>\n")
+}
+
+// A pipe table with a cell for each square, and empty cells off the board. The columns are padded
+// to one width so that the table lines up as text too. Tables need a header, which is left empty.
+// The uncovered cells are bold.
+fn markdown(out: &mut impl Write, board: &DecodingBoard) -> fmt::Result {
+    let layout = layout(board);
+    let rows = layout
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&cell| match cell {
+                    None => String::new(),
+                    Some((Square::Covered { tile_id }, _)) => tile_name(tile_id),
+                    Some((Square::Uncovered, Some(label))) => {
+                        format!("**{}**", markdown_escape(&label_text(label)))
+                    }
+                    Some((Square::Uncovered, None)) => "**#**".to_owned(),
+                    Some((Square::Empty, _)) => ".".to_owned(),
+                    Some((Square::Unknown, _)) => "?".to_owned(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let columns = layout.first().map_or(0, Vec::len);
+    // The separator needs at least three dashes
+    let width = rows
+        .iter()
+        .flatten()
+        .map(|cell| cell.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+
+    let header = vec![String::new(); columns];
+    let separator = vec!["-".repeat(width); columns];
+    for row in [&header, &separator].into_iter().chain(&rows) {
+        out.write_char('|')?;
+        for cell in row {
+            write!(out, " {cell:width$} |")?;
+        }
+        out.write_char('\n')?;
+    }
+    // Ends the table, which would otherwise run on into whatever follows
+    out.write_char('\n')
+}
+
+// Escapes the characters which would end a table cell or start emphasis
+fn markdown_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if matches!(ch, '|' | '*' | '_' | '\\' | '`') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+// Glyphs for the labels in PNG images, five rows of three pixels each with the leftmost pixel in
+// the highest bit
+#[cfg(feature = "png")]
+const FONT: [(char, [u8; 5]); 36] = [
+    ('0', [7, 5, 5, 5, 7]),
+    ('1', [2, 6, 2, 2, 7]),
+    ('2', [7, 1, 7, 4, 7]),
+    ('3', [7, 1, 7, 1, 7]),
+    ('4', [5, 5, 7, 1, 1]),
+    ('5', [7, 4, 7, 1, 7]),
+    ('6', [7, 4, 7, 5, 7]),
+    ('7', [7, 1, 1, 1, 1]),
+    ('8', [7, 5, 7, 5, 7]),
+    ('9', [7, 5, 7, 1, 7]),
+    ('A', [2, 5, 7, 5, 5]),
+    ('B', [6, 5, 6, 5, 6]),
+    ('C', [3, 4, 4, 4, 3]),
+    ('D', [6, 5, 5, 5, 6]),
+    ('E', [7, 4, 6, 4, 7]),
+    ('F', [7, 4, 6, 4, 4]),
+    ('G', [3, 4, 5, 5, 3]),
+    ('H', [5, 5, 7, 5, 5]),
+    ('I', [7, 2, 2, 2, 7]),
+    ('J', [1, 1, 1, 5, 2]),
+    ('K', [5, 5, 6, 5, 5]),
+    ('L', [4, 4, 4, 4, 7]),
+    ('M', [5, 7, 5, 5, 5]),
+    ('N', [5, 7, 7, 7, 5]),
+    ('O', [2, 5, 5, 5, 2]),
+    ('P', [6, 5, 6, 4, 4]),
+    ('Q', [2, 5, 5, 7, 3]),
+    ('R', [6, 5, 6, 5, 5]),
+    ('S', [3, 4, 2, 1, 6]),
+    ('T', [7, 2, 2, 2, 2]),
+    ('U', [5, 5, 5, 5, 7]),
+    ('V', [5, 5, 5, 5, 2]),
+    ('W', [5, 5, 7, 7, 5]),
+    ('X', [5, 5, 2, 5, 5]),
+    ('Y', [5, 5, 2, 2, 2]),
+    ('Z', [7, 1, 2, 4, 7]),
+];
+
+#[cfg(feature = "png")]
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+#[cfg(feature = "png")]
+impl Canvas {
+    fn fill(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), color: [u8; 3]) {
+        for y in y0..y1 {
+            self.pixels[y * self.width + x0..y * self.width + x1].fill(color);
+        }
+    }
+
+    // Writes `text` centered in the square cell at `corner`. Characters the font doesn't have are
+    // left as gaps.
+    fn text(&mut self, (x, y): (usize, usize), scale: usize, text: &str) {
+        let chars = text.to_uppercase().chars().collect::<Vec<_>>();
+        if chars.is_empty() {
+            return;
+        }
+        let columns = chars.len() * 4 - 1;
+        let pixel = (scale * 3 / 4 / columns).min(scale / 2 / 5).max(1);
+        let left = x + scale.saturating_sub(columns * pixel) / 2;
+        let top = y + scale.saturating_sub(5 * pixel) / 2;
+
+        for (index, ch) in chars.into_iter().enumerate() {
+            let Some((_, rows)) = FONT.iter().find(|&&(glyph, _)| glyph == ch) else {
+                continue;
+            };
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (4 >> col) != 0 {
+                        let x = left + (index * 4 + col) * pixel;
+                        let y = top + row * pixel;
+                        self.fill((x, y), (x + pixel, y + pixel), [0; 3]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+fn rgb(hex: &str) -> [u8; 3] {
+    let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).expect("colors are hex");
+    [channel(1), channel(3), channel(5)]
+}
+
+#[cfg(feature = "png")]
+fn png(board: &DecodingBoard, scale: u32) -> Vec<u8> {
+    let canvas = raster(board, scale);
+    crate::png::encode(canvas.width as u32, canvas.height as u32, &canvas.pixels)
+}
+
+// The board drawn over and over into one animated PNG, as it changes during a search
+#[cfg(feature = "png")]
+pub struct Recording {
+    scale: u32,
+    animation: crate::png::Animation,
+}
+
+#[cfg(feature = "png")]
+impl Recording {
+    // Every frame is the size of the board, drawn with `scale` pixels per cell
+    pub fn new(board: &DecodingBoard, scale: u32) -> Self {
+        let canvas = raster(board, scale);
+        Self {
+            scale,
+            animation: crate::png::Animation::new(canvas.width as u32, canvas.height as u32),
+        }
+    }
+
+    pub fn push(&mut self, board: &DecodingBoard) {
+        self.animation.push(&raster(board, self.scale).pixels);
+    }
+
+    pub fn frames(&self) -> usize {
+        self.animation.len()
+    }
+
+    // The image showing each frame for `delay` and holding the last for `hold`. There has to have
+    // been at least one frame.
+    pub fn finish(self, delay: std::time::Duration, hold: std::time::Duration) -> Vec<u8> {
+        let millis =
+            |duration: std::time::Duration| duration.as_millis().min(u16::MAX as u128) as u16;
+        self.animation.finish(millis(delay), millis(hold))
+    }
+}
+
+// Draws the board as an image with `scale` pixels per cell. Tiles are filled with their colors,
+// walls are drawn only between squares which differ, and the uncovered cells show their labels.
+#[cfg(feature = "png")]
+fn raster(board: &DecodingBoard, scale: u32) -> Canvas {
+    const WHITE: [u8; 3] = [0xff; 3];
+
+    let layout = layout(board);
+    let scale = scale as usize;
+    let margin = scale / 4;
+    let height = layout.len() * scale + 2 * margin;
+    let width = layout.first().map_or(0, Vec::len) * scale + 2 * margin;
+    let mut canvas = Canvas {
+        width,
+        height,
+        pixels: vec![WHITE; width * height],
+    };
+    let corner = |row: usize, col: usize| (margin + col * scale, margin + row * scale);
+
+    for (row_idx, row) in layout.iter().enumerate() {
+        for (col_idx, &cell) in row.iter().enumerate() {
+            let Some((square, label)) = cell else {
+                continue;
+            };
+            let (x, y) = corner(row_idx, col_idx);
+            let color = match square {
+                Square::Covered { tile_id } => rgb(FILLS[tile_id as usize % FILLS.len()]),
+                _ => WHITE,
+            };
+            canvas.fill((x, y), (x + scale, y + scale), color);
+            if let (Square::Uncovered, Some(label)) = (square, label) {
+                canvas.text((x, y), scale, &label_text(label));
+            }
+        }
+    }
+
+    // The walls go on last so that no cell is filled over them. Each is centered on the edge, and
+    // the margin leaves room for the half outside the board.
+    let half = (scale / 12).max(2) / 2;
+    for (row_idx, row) in layout.iter().enumerate() {
+        for (col_idx, &cell) in row.iter().enumerate() {
+            let Some((square, _)) = cell else {
+                continue;
+            };
+            let (x, y) = corner(row_idx, col_idx);
+            // `square_at` counts from one
+            let walls = [
+                ((row_idx, col_idx + 1), (x, y), (x + scale, y)),
+                (
+                    (row_idx + 1, col_idx + 2),
+                    (x + scale, y),
+                    (x + scale, y + scale),
+                ),
+                (
+                    (row_idx + 2, col_idx + 1),
+                    (x, y + scale),
+                    (x + scale, y + scale),
+                ),
+                ((row_idx + 1, col_idx), (x, y), (x, y + scale)),
+            ];
+            for ((row, col), from, to) in walls {
+                if square_at(&layout, row, col) != Some(square) {
+                    canvas.fill(
+                        (from.0 - half, from.1 - half),
+                        (to.0 + half, to.1 + half),
+                        [0; 3],
+                    );
+                }
+            }
+        }
+    }
+
+    canvas
+}
+
+// The box-drawing character joining the given edges at a vertex
+fn junction(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (true, true, false, false) | (true, false, false, false) | (false, true, false, false) => {
+            '│'
+        }
+        (false, false, true, true) | (false, false, true, false) | (false, false, false, true) => {
+            '─'
+        }
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+// What `--format json` emits for each solution, with every point in board coordinates as `[x, y]`:
+//
+//   {
+//     "date": {"month": 10, "day": 13},
+//     "board": {"width": 7, "height": 7},
+//     "tiles": [{"id": 0, "cells": [[x, y], ...]}, ...],
+//     "uncovered": [[x, y], ...],
+//     "extra": [[x, y], ...]
+//   }
+//
+// With the `serde` feature it's written by serde, and can be read back the same way. Otherwise
+// it's written by hand in the same form.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonSolution {
+    // Read from the labels of the uncovered cells, and left out if they don't show a month and a
+    // day
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub date: Option<JsonDate>,
+    pub board: JsonBoard,
+    pub tiles: Vec<JsonTile>,
+    pub uncovered: Vec<[i32; 2]>,
+    // The cells left uncovered besides the date, which is only there if there are any
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub extra: Vec<[i32; 2]>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonDate {
+    // Counting from 1 for January
+    pub month: i32,
+    pub day: u8,
+}
+
+// The size of the board's bounding box
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonBoard {
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonTile {
+    pub id: usize,
+    // In reading order
+    pub cells: Vec<[i32; 2]>,
+}
+
+impl JsonSolution {
+    pub fn new<B: BitBoard>(board: &DecodingBoard, solution: &Solution<B>) -> Self {
+        let labels = labels(board);
+        let mut month = None;
+        let mut day = None;
+        for point in solution.goal_cells() {
+            match labels.get(&point) {
+                Some(Label::Month(label)) => month = Some(*label as i32 + 1),
+                Some(Label::Day(label)) => day = Some(*label),
+                _ => (),
+            }
+        }
+
+        let points = board.points().iter().flatten();
+        let extent = |coord: fn(&Point) -> i32| {
+            let min = points.clone().map(coord).min().unwrap_or(0);
+            let max = points.clone().map(coord).max().unwrap_or(-1);
+            max - min + 1
+        };
+        let cells = |points: Vec<Point>| {
+            points
+                .into_iter()
+                .map(|point| [point.x, point.y])
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            date: month.zip(day).map(|(month, day)| JsonDate { month, day }),
+            board: JsonBoard {
+                width: extent(|point| point.x),
+                height: extent(|point| point.y),
+            },
+            tiles: (0..solution.tiles().len())
+                .map(|id| JsonTile {
+                    id,
+                    cells: cells(reading_order(solution.cells_of(id))),
+                })
+                .collect(),
+            uncovered: cells(reading_order(solution.goal_cells())),
+            extra: cells(reading_order(solution.extra_cells())),
+        }
+    }
+
+    // The same JSON serde writes, without needing it
+    #[cfg(any(test, not(feature = "serde")))]
+    fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        if let Some(JsonDate { month, day }) = self.date {
+            write!(out, "\"date\":{{\"month\":{month},\"day\":{day}}},").unwrap();
+        }
+        write!(
+            out,
+            "\"board\":{{\"width\":{},\"height\":{}}},",
+            self.board.width, self.board.height
+        )
+        .unwrap();
+
+        out.push_str("\"tiles\":[");
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            write!(out, "{{\"id\":{},\"cells\":", tile.id).unwrap();
+            json_points(&mut out, &tile.cells);
+            out.push('}');
+        }
+        out.push_str("],\"uncovered\":");
+        json_points(&mut out, &self.uncovered);
+        if !self.extra.is_empty() {
+            out.push_str(",\"extra\":");
+            json_points(&mut out, &self.extra);
+        }
+        out.push('}');
+        out
+    }
+}
+
+fn json<B: BitBoard>(board: &DecodingBoard, solution: &Solution<B>) -> String {
+    let json = JsonSolution::new(board, solution);
+    #[cfg(feature = "serde")]
+    let json = serde_json::to_string(&json).expect("solutions can always be written as JSON");
+    #[cfg(not(feature = "serde"))]
+    let json = json.to_json();
+    json + "\n"
+}
+
+// One `tile_id: (x,y) ...` line per tile followed by an `uncovered:` line, all in board
+// coordinates. Cells left uncovered besides the date are listed on a final `extra:` line.
+pub fn coords<B: BitBoard>(solution: &Solution<B>) -> String {
+    let mut out = String::new();
+
+    let extra = sorted(solution.extra_cells());
+    let extra = (!extra.is_empty()).then(|| ("extra".to_owned(), extra));
+    let lines = (0..solution.tiles().len())
+        .map(|id| (id.to_string(), sorted(solution.cells_of(id))))
+        .chain([("uncovered".to_owned(), sorted(solution.goal_cells()))])
+        .chain(extra);
+    for (label, points) in lines {
+        out.push_str(&label);
+        out.push(':');
+        for point in points {
+            write!(out, " ({},{})", point.x, point.y).unwrap();
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+// The side of a cell in an SVG, in pixels
+const SVG_CELL: i32 = 40;
+
+// Room around the board in an SVG, so that the outermost strokes aren't cut off
+const SVG_MARGIN: i32 = 4;
+
+// Fill colors for the tiles in SVG and HTML, given out by tile id like `PALETTE`
+const FILLS: [&str; 12] = [
+    "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#46f0f0", "#f032e6",
+    "#bcf60c", "#fabebe", "#008080", "#e6beff",
+];
+
+// Draws the solution as an SVG picture. Each tile is a single path around its outline, and the
+// cells left uncovered are outlined squares showing their labels.
+pub fn svg<B: BitBoard>(board: &DecodingBoard, solution: &Solution<B>) -> String {
+    let points = board.points().iter().flatten();
+    let min_x = points.clone().map(|point| point.x).min().unwrap_or(0);
+    let max_x = points.clone().map(|point| point.x).max().unwrap_or(-1);
+    let min_y = points.clone().map(|point| point.y).min().unwrap_or(0);
+    let max_y = points.clone().map(|point| point.y).max().unwrap_or(-1);
+    let labels = labels(board);
+
+    // Board points to the column and row of the picture, which counts rows down from the top
+    let cell = |point: Point| (point.x - min_x, max_y - point.y);
+    let pixels = |units: i32| units * SVG_CELL + SVG_MARGIN;
+
+    let width = pixels(max_x - min_x + 1) + SVG_MARGIN;
+    let height = pixels(max_y - min_y + 1) + SVG_MARGIN;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n"
+    );
+
+    for id in 0..solution.tiles().len() {
+        let cells = solution.cells_of(id).map(cell).collect::<HashSet<_>>();
+        let mut data = String::new();
+        for outline in outlines(&cells) {
+            for (index, (x, y)) in outline.into_iter().enumerate() {
+                let command = if index == 0 { 'M' } else { 'L' };
+                write!(data, "{command}{},{} ", pixels(x), pixels(y)).unwrap();
+            }
+            data.push_str("Z ");
+        }
+        writeln!(
+            out,
+            "  <path d=\"{}\" fill=\"{}\" stroke=\"black\" stroke-width=\"2\"/>",
+            data.trim_end(),
+            FILLS[id % FILLS.len()]
+        )
+        .unwrap();
+    }
+
+    for point in reading_order(solution.uncovered_cells()) {
+        let (x, y) = cell(point);
+        writeln!(
+            out,
+            "  <rect x=\"{}\" y=\"{}\" width=\"{SVG_CELL}\" height=\"{SVG_CELL}\" fill=\"none\" \
+             stroke=\"black\" stroke-width=\"2\"/>",
+            pixels(x),
+            pixels(y)
+        )
+        .unwrap();
+        if let Some(label) = labels.get(&point) {
+            writeln!(
+                out,
+                "  <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                pixels(x) + SVG_CELL / 2,
+                pixels(y) + SVG_CELL / 2,
+                xml_escape(&label_text(label))
+            )
+            .unwrap();
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+// The closed outlines around a set of cells, given as `(column, row)` with rows counting down,
+// as the corners where each one turns. Every edge between a cell in the set and one outside it
+// is walked with the set on its right, so the outside runs clockwise and any holes run the other
+// way, which is all a fill needs to leave the holes empty.
+fn outlines(cells: &HashSet<(i32, i32)>) -> Vec<Vec<(i32, i32)>> {
+    // Each boundary edge, keyed by the corner it starts from. A corner where two cells of the set
+    // only touch diagonally starts two edges.
+    let mut edges = BTreeMap::<(i32, i32), Vec<(i32, i32)>>::new();
+    for &(x, y) in cells {
+        let sides = [
+            ((x, y - 1), (x, y), (x + 1, y)),
+            ((x + 1, y), (x + 1, y), (x + 1, y + 1)),
+            ((x, y + 1), (x + 1, y + 1), (x, y + 1)),
+            ((x - 1, y), (x, y + 1), (x, y)),
+        ];
+        for (neighbor, from, to) in sides {
+            if !cells.contains(&neighbor) {
+                edges.entry(from).or_default().push(to);
+            }
+        }
+    }
+
+    let mut outlines = Vec::new();
+    while let Some((&start, _)) = edges.iter().find(|(_, ends)| !ends.is_empty()) {
+        let mut outline = vec![start];
+        let mut corner = start;
+        loop {
+            let ends = edges.get_mut(&corner).expect("every edge leads to another");
+            let Some(next) = ends.pop() else {
+                break;
+            };
+            // Only the corners where the direction changes are kept
+            if let [.., before, last] = outline[..] {
+                let direction = |(x0, y0): (i32, i32), (x1, y1): (i32, i32)| {
+                    ((x1 - x0).signum(), (y1 - y0).signum())
+                };
+                if direction(before, last) == direction(last, next) {
+                    outline.pop();
+                }
+            }
+            outline.push(next);
+            corner = next;
+        }
+        // The walk ends back at the start, which the path's `Z` closes instead
+        outline.pop();
+        outlines.push(outline);
+    }
+    outlines
+}
+
+// The labeled squares of the board, by their points
+fn labels(board: &DecodingBoard) -> HashMap<Point, &Label> {
+    board
+        .points()
+        .iter()
+        .flatten()
+        .zip(board.labels().iter().flatten())
+        .filter_map(|(&point, label)| Some((point, label.as_ref()?)))
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn sorted(points: impl Iterator<Item = Point>) -> Vec<Point> {
+    let mut points = points.collect::<Vec<_>>();
+    points.sort_by_key(|point| (point.x, point.y));
+    points
+}
+
+// In the order the board is drawn
+fn reading_order(points: impl Iterator<Item = Point>) -> Vec<Point> {
+    let mut points = points.collect::<Vec<_>>();
+    points.sort_by(Point::cmp_reading_order);
+    points
+}
+
+#[cfg(any(test, not(feature = "serde")))]
+fn json_points(out: &mut String, points: &[[i32; 2]]) {
+    out.push('[');
+    for (index, [x, y]) in points.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        write!(out, "[{x},{y}]").unwrap();
+    }
+    out.push(']');
+}
+
+// A small drawing of the tile with `#` for each cell, in the format the tile loader reads
+pub fn tile(tile: &Tile) -> String {
+    let points = tile.points();
+    let bounds = tile.bounding_box();
+
+    let mut out = String::new();
+    for y in (bounds.min().y..=bounds.max().y).rev() {
+        let row = (bounds.min().x..=bounds.max().x)
+            .map(|x| {
+                if points.contains(&Point { x, y }) {
+                    '#'
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>();
+        writeln!(out, "{}", row.trim_end()).unwrap();
+    }
+
+    out
+}
+
+// The space between blocks laid out side by side
+const GUTTER: usize = 3;
+
+// The narrowest a legend is wrapped at
+const LEGEND_WIDTH: usize = 32;
+
+// Each tile's letter over its shape as it was defined, side by side and wrapped at `width`. With
+// colors, the shape is painted the way the tile is on the board.
+fn legend(tiles: &[Tile], style: Style, width: usize) -> String {
+    let blocks = tiles
+        .iter()
+        .enumerate()
+        .map(|(id, shape)| {
+            let square = Some(Square::Covered { tile_id: id as u8 });
+            let mut block = format!("{}\n", tile_name(id as u8));
+            for line in tile(shape).lines() {
+                for ch in line.chars() {
+                    match ch {
+                        '#' => paint(&mut block, style, square, "#").unwrap(),
+                        ch => block.push(ch),
+                    }
+                }
+                block.push('\n');
+            }
+            block
+        })
+        .collect::<Vec<_>>();
+    columns(&blocks, blocks.len(), Some(width))
+}
+
+// Lays out blocks of text side by side, at most `count` to a row and fewer if they'd go past
+// `width`. Every column is as wide as the widest block's widest line, so the drawings line up.
+pub fn columns(blocks: &[String], count: usize, width: Option<usize>) -> String {
+    let block_width = blocks
+        .iter()
+        .flat_map(|block| block.lines())
+        .map(visible_width)
+        .max()
+        .unwrap_or(0);
+    let count = match width {
+        Some(width) => count.min((width + GUTTER) / (block_width + GUTTER)),
+        None => count,
+    };
+
+    let mut out = String::new();
+    for (index, row) in blocks.chunks(count.max(1)).enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+
+        let row = row
+            .iter()
+            .map(|block| block.lines().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let height = row.iter().map(Vec::len).max().unwrap_or(0);
+        for line in 0..height {
+            let mut text = String::new();
+            for (column, lines) in row.iter().enumerate() {
+                let cell = lines.get(line).copied().unwrap_or("");
+                if column > 0 {
+                    let previous = row[column - 1].get(line).copied().unwrap_or("");
+                    let padding = block_width - visible_width(previous) + GUTTER;
+                    text.push_str(&" ".repeat(padding));
+                }
+                text.push_str(cell);
+            }
+            writeln!(out, "{}", text.trim_end()).unwrap();
+        }
+    }
+
+    out
+}
+
+// How many columns the text takes up on a terminal, leaving out the color codes `paint` adds
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            chars.by_ref().find(|&ch| ch == 'm');
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => write!(out, "\\u{:04x}", ch as u32).unwrap(),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::{Date, Month},
+        entity::EncodingBoard,
+        puzzle::Puzzle,
+        variant,
+    };
+
+    fn classic() -> Puzzle {
+        Puzzle::from_variant(variant::classic()).unwrap()
+    }
+
+    fn solution(puzzle: &Puzzle, month: Month, day: i32) -> Solution<u64> {
+        let date = Date {
+            year: None,
+            month,
+            day,
+        };
+        let goal = puzzle.board().date_points(date);
+        puzzle.solver().solve(&goal).unwrap().unwrap()
+    }
+
+    fn unicode(month: Month, day: i32) -> String {
+        let puzzle = classic();
+        let mut board = DecodingBoard::from(puzzle.board());
+        Format::Unicode(Style::PLAIN)
+            .render_solution(&mut board, &solution(&puzzle, month, day))
+            .unwrap()
+    }
+
+    // The month strip is a cell shorter than the rows below it, so its right end and the bottom
+    // right of the board, which is cut off the same way, need junctions of their own
+    #[test]
+    fn unicode_notched_corners() {
+        let expected = "\
+┌───────────┬───────┬───┐
+│ A   A   A │ B   B │ C │
+│   ┌───────┼───┐   │   │
+│ A │ H   H │OCT│ B │ C │
+│   │   ┌───┴───┤   │   └───┐
+│ A │ H │ D   D │ B │ C   C │
+├───┘   │       │   ├───┐   │
+│ H   H │ D   D │ B │13 │ C │
+├───────┤       ├───┴───┴───┤
+│ F   F │ D   D │ E   E   E │
+│   ┌───┴───────┴───┐       │
+│ F │ G   G   G   G │ E   E │
+│   └───┐   ┌───────┴───────┘
+│ F   F │ G │
+└───────┴───┘
+";
+        assert_eq!(unicode(Month::October, 13), expected);
+    }
+
+    // With the month at the end of the strip uncovered, its walls meet the notch below it
+    #[test]
+    fn unicode_uncovered_notch() {
+        let expected = "\
+┌───────────┬───┬───────┐
+│ A   A   A │ E │ F   F │
+│   ┌───┬───┘   │   ┌───┤
+│ A │ C │ E   E │ F │DEC│
+│   │   │       │   └───┼───┐
+│ A │ C │ E   E │ F   F │ B │
+├───┤   └───┬───┴───────┘   │
+│ G │ C   C │ B   B   B   B │
+│   └───┐   ├───┬───────────┤
+│ G   G │ C │ H │ D   D   D │
+│   ┌───┴───┘   │           │
+│ G │ H   H   H │ D   D   D │
+│   │   ┌───┬───┴───────────┘
+│ G │ H │31 │
+└───┴───┴───┘
+";
+        assert_eq!(unicode(Month::December, 31), expected);
+    }
+
+    // Cells with the tile covering them, or `None` if they're left uncovered
+    type Region<'a> = (Option<u8>, &'a [(i32, i32)]);
+
+    // A board of just the given cells, covered as they say
+    fn shaped_board(tiles: &[Region]) -> DecodingBoard {
+        let point = |&(x, y): &(i32, i32)| Point { x, y };
+        let cells = tiles.iter().flat_map(|(_, cells)| cells.iter().map(point));
+        let encoding = EncodingBoard::<u64>::from_points(cells).unwrap();
+        let mut board = DecodingBoard::from(&encoding);
+        for &(tile_id, cells) in tiles {
+            let enc = encoding.encode(cells.iter().map(point)).unwrap();
+            board.decode(enc, tile_id, false).unwrap();
+        }
+        board
+    }
+
+    // The middle row sticks out past the rows above and below it on both sides
+    #[test]
+    fn plus_shaped_board() {
+        let board = shaped_board(&[
+            (Some(0), &[(1, 0), (1, 1), (1, 2)]),
+            (Some(1), &[(0, 1)]),
+            (None, &[(2, 1)]),
+        ]);
+
+        let ascii = "    +---+
+    | A |
++---+   +---+
+| B | A | # |
++---+   +---+
+    | A |
+    +---+
+";
+        let unicode = "    ┌───┐
+    │ A │
+┌───┤   ├───┐
+│ B │ A │ # │
+└───┤   ├───┘
+    │ A │
+    └───┘
+";
+        assert_eq!(Format::Ascii(Style::PLAIN).render(&board).unwrap(), ascii);
+        assert_eq!(
+            Format::Unicode(Style::PLAIN).render(&board).unwrap(),
+            unicode
+        );
+    }
+
+    #[test]
+    fn board_wider_at_the_bottom() {
+        let board = shaped_board(&[
+            (Some(0), &[(1, 1), (1, 0)]),
+            (Some(1), &[(0, 0)]),
+            (Some(2), &[(2, 0)]),
+        ]);
+
+        let ascii = "    +---+
+    | A |
++---+   +---+
+| B | A | C |
++---+---+---+
+";
+        let unicode = "    ┌───┐
+    │ A │
+┌───┤   ├───┐
+│ B │ A │ C │
+└───┴───┴───┘
+";
+        assert_eq!(Format::Ascii(Style::PLAIN).render(&board).unwrap(), ascii);
+        assert_eq!(
+            Format::Unicode(Style::PLAIN).render(&board).unwrap(),
+            unicode
+        );
+    }
+
+    // The middle row is a single cell at the left, so the rows above and below it reach past it
+    #[test]
+    fn c_shaped_board() {
+        let board = shaped_board(&[
+            (Some(0), &[(0, 2), (1, 2), (2, 2)]),
+            (Some(1), &[(0, 1), (0, 0), (1, 0)]),
+            (None, &[(2, 0)]),
+        ]);
+        let ascii = "\
++---+---+---+
+| A   A   A |
++---+---+---+
+| B |
++   +---+---+
+| B   B | # |
++---+---+---+
+";
+        let unicode = "\
+┌───────────┐
+│ A   A   A │
+├───┬───────┘
+│ B │
+│   └───┬───┐
+│ B   B │ # │
+└───────┴───┘
+";
+        assert_eq!(Format::Ascii(Style::PLAIN).render(&board).unwrap(), ascii);
+        assert_eq!(
+            Format::Unicode(Style::PLAIN).render(&board).unwrap(),
+            unicode
+        );
+    }
+
+    // The hole in the middle splits its row in two, and gets walls all the way around
+    #[test]
+    fn board_with_a_hole() {
+        let board = shaped_board(&[
+            (Some(0), &[(0, 2), (1, 2), (2, 2), (2, 1)]),
+            (Some(1), &[(0, 1), (0, 0), (1, 0)]),
+            (None, &[(2, 0)]),
+        ]);
+        let ascii = "\
++---+---+---+
+| A   A   A |
++---+---+   +
+| B |   | A |
++   +---+---+
+| B   B | # |
++---+---+---+
+";
+        let unicode = "\
+┌───────────┐
+│ A   A   A │
+├───┬───┐   │
+│ B │   │ A │
+│   └───┼───┤
+│ B   B │ # │
+└───────┴───┘
+";
+        assert_eq!(Format::Ascii(Style::PLAIN).render(&board).unwrap(), ascii);
+        assert_eq!(
+            Format::Unicode(Style::PLAIN).render(&board).unwrap(),
+            unicode
+        );
+    }
+
+    // Twice the signed area inside the corners, positive when they run clockwise on the screen
+    fn doubled_area(corners: &[(i32, i32)]) -> i32 {
+        let next = corners.iter().cycle().skip(1);
+        corners
+            .iter()
+            .zip(next)
+            .map(|(&(x0, y0), &(x1, y1))| x0 * y1 - x1 * y0)
+            .sum()
+    }
+
+    // Reads the corners of each outline back out of a path's `d` attribute
+    fn path_outlines(data: &str) -> Vec<Vec<(i32, i32)>> {
+        data.split('Z')
+            .map(str::trim)
+            .filter(|outline| !outline.is_empty())
+            .map(|outline| {
+                outline
+                    .split_whitespace()
+                    .map(|corner| {
+                        let (x, y) = corner[1..].split_once(',').unwrap();
+                        (x.parse().unwrap(), y.parse().unwrap())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn svg_has_a_path_per_tile_and_a_square_per_uncovered_cell() {
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let board = DecodingBoard::from(puzzle.board());
+        let svg = svg(&board, &solution);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        let paths = svg
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("<path d=\""))
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), puzzle.tiles().len());
+        assert_eq!(svg.matches("<rect ").count(), 2);
+        assert!(svg.contains(">OCT</text>"));
+        assert!(svg.contains(">13</text>"));
+
+        // Each tile is one outline around all of its cells, which are squares of `SVG_CELL`
+        for (id, data) in paths.into_iter().enumerate() {
+            let outlines = path_outlines(data);
+            assert_eq!(outlines.len(), 1, "tile {id}");
+            let cells = solution.cells_of(id).count() as i32;
+            assert_eq!(
+                doubled_area(&outlines[0]),
+                2 * cells * SVG_CELL * SVG_CELL,
+                "tile {id}"
+            );
+        }
+    }
+
+    // A ring of cells has an outline around the outside and one the other way around the hole
+    #[test]
+    fn outlines_of_a_ring() {
+        let cells = (0..3)
+            .flat_map(|x| (0..3).map(move |y| (x, y)))
+            .filter(|&cell| cell != (1, 1))
+            .collect::<HashSet<_>>();
+        let mut outlines = outlines(&cells);
+        outlines.sort_by_key(|outline| -doubled_area(outline));
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(doubled_area(&outlines[0]), 2 * 9);
+        assert_eq!(doubled_area(&outlines[1]), -2);
+    }
+
+    // Each block ends with a blank line, to keep it apart from whatever comes after it
+    #[test]
+    fn markdown_snapshot() {
+        let table = "\
+|         |         |         |         |         |         |         |
+| ------- | ------- | ------- | ------- | ------- | ------- | ------- |
+| A       | A       | A       | B       | B       | C       |         |
+| A       | H       | H       | **OCT** | B       | C       |         |
+| A       | H       | D       | D       | B       | C       | C       |
+| H       | H       | D       | D       | B       | **13**  | C       |
+| F       | F       | D       | D       | E       | E       | E       |
+| F       | G       | G       | G       | G       | E       | E       |
+| F       | F       | G       |         |         |         |         |
+
+";
+        let grid = "\
+```text
+AAABBC
+AHH#BC
+AHDDBCC
+HHDDB#C
+FFDDEEE
+FGGGGEE
+FFG
+```
+
+";
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        for (format, expected) in [(Format::Markdown, table), (Format::MarkdownGrid, grid)] {
+            let mut board = DecodingBoard::from(puzzle.board());
+            assert_eq!(
+                format.render_solution(&mut board, &solution).unwrap(),
+                expected
+            );
+            assert_eq!(
+                format.heading("Solution for October 13:").unwrap(),
+                "### Solution for October 13:\n\n"
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_holes_are_empty_cells() {
+        let board = shaped_board(&[
+            (Some(0), &[(0, 2), (1, 2), (2, 2), (2, 1)]),
+            (Some(1), &[(0, 1), (0, 0), (1, 0)]),
+            (None, &[(2, 0)]),
+        ]);
+        let expected = "\
+|       |       |       |
+| ----- | ----- | ----- |
+| A     | A     | A     |
+| B     |       | A     |
+| B     | B     | **#** |
+
+";
+        assert_eq!(Format::Markdown.render(&board).unwrap(), expected);
+    }
+
+    // Displaying the board gives the plain ASCII drawing, the same as writing it out
+    #[test]
+    fn display_snapshot() {
+        let expected = "\
++---+---+---+---+---+---+
+| A   A   A | B   B | C |
++   +---+---+---+   +   +
+| A | H   H |OCT| B | C |
++   +   +---+---+   +   +---+
+| A | H | D   D | B | C   C |
++---+   +   +   +   +---+   +
+| H   H | D   D | B |13 | C |
++---+---+   +   +---+---+---+
+| F   F | D   D | E   E   E |
++   +---+---+---+---+   +   +
+| F | G   G   G   G | E   E |
++   +---+   +---+---+---+---+
+| F   F | G |
++---+---+---+
+";
+        let puzzle = classic();
+        let mut board = DecodingBoard::from(puzzle.board());
+        board.draw(&solution(&puzzle, Month::October, 13)).unwrap();
+        assert_eq!(board.to_string(), expected);
+
+        let mut out = Vec::new();
+        board.render_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn only_drawings_render_from_the_board() {
+        let puzzle = classic();
+        let board = DecodingBoard::from(puzzle.board());
+        for format in [Format::Json, Format::Coords, Format::Svg] {
+            assert_eq!(format.render(&board), Err(RenderError::NeedsSolution));
+            let error = format.render_to(&board, &mut Vec::new()).unwrap_err();
+            assert_eq!(error.to_string(), RenderError::NeedsSolution.to_string());
+        }
+        assert!(Format::Ascii(Style::PLAIN).render(&board).is_ok());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn images_are_not_text() {
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let mut board = DecodingBoard::from(puzzle.board());
+        let format = Format::Png(DEFAULT_SCALE);
+        assert_eq!(format.render(&board), Err(RenderError::NotText));
+        assert_eq!(
+            format.render_solution(&mut board, &solution),
+            Err(RenderError::NotText)
+        );
+    }
+
+    // The board is 7 cells on a side, with a quarter of a cell of margin all around
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_smoke_test() {
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let mut board = DecodingBoard::from(puzzle.board());
+        let mut png = Vec::new();
+        Format::Png(16)
+            .render_solution_to(&mut board, &solution, &mut png)
+            .unwrap();
+
+        let (width, height, pixels) = crate::png::tests::decode(&png);
+        assert_eq!((width, height), (7 * 16 + 8, 7 * 16 + 8));
+        // A color for each tile, the white background and the black walls and labels
+        let colors = pixels.into_iter().collect::<HashSet<_>>();
+        assert!(colors.len() >= puzzle.tiles().len() + 2, "{}", colors.len());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn empty_labels_draw_nothing() {
+        let mut canvas = Canvas {
+            width: 16,
+            height: 16,
+            pixels: vec![[0xff; 3]; 256],
+        };
+        canvas.text((0, 0), 16, "");
+        assert!(canvas.pixels.iter().all(|&pixel| pixel == [0xff; 3]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_is_written_the_same_with_or_without_serde() {
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let board = DecodingBoard::from(puzzle.board());
+        let json = JsonSolution::new(&board, &solution);
+        assert_eq!(serde_json::to_string(&json).unwrap(), json.to_json());
+    }
+
+    // What `--format json` writes can be read back and still passes the verifier
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_through_verify() {
+        use crate::verify::{self, Piece};
+
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let mut board = DecodingBoard::from(puzzle.board());
+        let text = Format::Json.render_solution(&mut board, &solution).unwrap();
+
+        let json = serde_json::from_str::<JsonSolution>(&text).unwrap();
+        assert_eq!(json.date, Some(JsonDate { month: 10, day: 13 }));
+        let point = |[x, y]: [i32; 2]| Point { x, y };
+        let pieces = json
+            .tiles
+            .into_iter()
+            .map(|tile| Piece {
+                label: tile.id.to_string(),
+                points: tile.cells.into_iter().map(point).collect(),
+            })
+            .collect::<Vec<_>>();
+        let uncovered = json.uncovered.into_iter().map(point).collect::<Vec<_>>();
+        assert_eq!(
+            verify::verify(
+                puzzle.board(),
+                puzzle.tiles(),
+                &uncovered,
+                &pieces,
+                puzzle.transforms()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn every_text_format_renders_a_solution() {
+        let puzzle = classic();
+        let solution = solution(&puzzle, Month::October, 13);
+        let mut board = DecodingBoard::from(puzzle.board());
+        for (name, format) in Format::NAMES {
+            let text = format.render_solution(&mut board, &solution);
+            assert!(text.is_ok_and(|text| !text.is_empty()), "{name}");
+        }
+    }
+}
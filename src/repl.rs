@@ -0,0 +1,153 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    cli::{self, Args, Command, Parsed},
+    error::Error,
+    BitBoard, Context,
+};
+
+const HELP: &str = "\
+Commands are written the same way as on the command line, for example:
+  solve oct 13 --all
+  count dec 25
+  heatmap --month feb
+
+The board and tiles are fixed for the whole session. Other commands:
+  tiles            Print the tiles being placed
+  ? or help        Print this message
+  quit or exit     Leave the REPL";
+
+// A single line typed at the prompt
+enum Line {
+    Empty,
+    Help,
+    Tiles,
+    Quit,
+    Run(Box<Args>),
+}
+
+// Reads and runs commands until `quit` or the end of input. Errors from individual commands are
+// printed rather than ending the session.
+pub fn run<B: BitBoard>(mut ctx: Context<B>) -> Result<(), Error> {
+    let mut lines = io::stdin().lock().lines();
+
+    loop {
+        print!("> ");
+        // A prompt that fails to show up isn't worth stopping over
+        io::stdout().flush().ok();
+
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        let line =
+            line.map_err(|error| Error::InvalidInput(format!("couldn't read a command: {error}")))?;
+
+        match parse_line(&line) {
+            Ok(Line::Empty) => (),
+            Ok(Line::Help) => println!("{HELP}"),
+            Ok(Line::Tiles) => crate::list_tiles(&ctx.tiles, ctx.transforms),
+            Ok(Line::Quit) => return Ok(()),
+            Ok(Line::Run(args)) => {
+                ctx.configure(&args);
+                if let Err(error) = crate::dispatch(&ctx, &args) {
+                    crate::print_error(&error);
+                }
+            }
+            Err(message) => crate::print_error(&Error::InvalidInput(message)),
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<Line, String> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+
+    let simple = match words.first() {
+        None => return Ok(Line::Empty),
+        Some(&"?" | &"help") => Line::Help,
+        Some(&"tiles") => Line::Tiles,
+        Some(&"quit" | &"exit") => Line::Quit,
+        Some(_) => {
+            return match cli::parse_args(words.iter().map(|&word| word.to_owned()))? {
+                Parsed::Help => Ok(Line::Help),
+                Parsed::Run(args) => check_args(args).map(Line::Run),
+            }
+        }
+    };
+
+    if words.len() > 1 {
+        return Err(format!("`{}` doesn't take any arguments", words[0]));
+    }
+    Ok(simple)
+}
+
+// Rejects the options which only make sense when starting the REPL
+fn check_args(args: Box<Args>) -> Result<Box<Args>, String> {
+    if args.command == Command::Repl {
+        return Err("already in the REPL".to_owned());
+    }
+    if args.command == Command::Serve {
+        return Err("`serve` can't be run from the REPL".to_owned());
+    }
+    if args.variant.is_some() || args.board.is_some() || args.tiles.is_some() || args.one_sided {
+        return Err(
+            "the puzzle can't be changed in the REPL, pass `--variant`, `--board`, `--tiles` or \
+             `--one-sided` when starting it instead"
+                .to_owned(),
+        );
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Month;
+
+    fn run_args(line: &str) -> Box<Args> {
+        match parse_line(line) {
+            Ok(Line::Run(args)) => args,
+            Ok(..) => panic!("`{line}` isn't a command to run"),
+            Err(message) => panic!("`{line}` was rejected: {message}"),
+        }
+    }
+
+    #[test]
+    fn commands_parse_like_the_command_line() {
+        let args = run_args("solve oct 13 --all");
+        assert!(args.command == Command::Solve);
+        assert_eq!((args.month, args.day), (Some(Month::October), Some(13)));
+        assert!(args.all);
+
+        let args = run_args("  count   dec 25 ");
+        assert!(args.command == Command::Count);
+        assert_eq!((args.month, args.day), (Some(Month::December), Some(25)));
+    }
+
+    #[test]
+    fn session_commands() {
+        assert!(matches!(parse_line(""), Ok(Line::Empty)));
+        assert!(matches!(parse_line("   "), Ok(Line::Empty)));
+        assert!(matches!(parse_line("?"), Ok(Line::Help)));
+        assert!(matches!(parse_line("help"), Ok(Line::Help)));
+        assert!(matches!(parse_line("solve --help"), Ok(Line::Help)));
+        assert!(matches!(parse_line("tiles"), Ok(Line::Tiles)));
+        assert!(matches!(parse_line("quit"), Ok(Line::Quit)));
+        assert!(matches!(parse_line("exit"), Ok(Line::Quit)));
+    }
+
+    #[test]
+    fn malformed_commands_are_rejected() {
+        let error = |line| match parse_line(line) {
+            Err(message) => message,
+            Ok(..) => panic!("`{line}` was accepted"),
+        };
+        assert_eq!(error("quit now"), "`quit` doesn't take any arguments");
+        assert_eq!(error("repl"), "already in the REPL");
+        assert_eq!(error("serve"), "`serve` can't be run from the REPL");
+        assert!(error("solve --board board.txt").starts_with("the puzzle can't be changed"));
+        assert!(parse_line("solve smarch 13").is_err());
+        assert!(parse_line("solve oct").is_err());
+        assert!(parse_line("frobnicate").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    bitboard::BitBoard,
+    entity::{EncodingBoard, Label, Point, PuzzleError, Tile, Transforms, AABB},
+    puzzle::Puzzle,
+};
+
+// Boards, tiles, and puzzles are written as the cells they have rather than how they're stored, so
+// that what's written stays readable as the encoding changes. Reading them back goes through the
+// same constructors as building them does, so nothing which couldn't be built is accepted.
+
+#[derive(Serialize, Deserialize)]
+struct AabbRepr {
+    min: Point,
+    max: Point,
+}
+
+impl Serialize for AABB {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AabbRepr {
+            min: self.min(),
+            max: self.max(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AABB {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = AabbRepr::deserialize(deserializer)?;
+        AABB::new(repr.min, repr.max).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TileRepr {
+    points: Vec<Point>,
+    transforms: Transforms,
+}
+
+impl Serialize for Tile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TileRepr {
+            points: self.points().to_vec(),
+            transforms: self.allowed_transforms(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = TileRepr::deserialize(deserializer)?;
+        let mut tile = Tile::new(repr.points).map_err(D::Error::custom)?;
+        tile.set_allowed_transforms(repr.transforms);
+        Ok(tile)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BoardRepr {
+    // In reading order, whichever order the bits are in
+    cells: Vec<Point>,
+    labels: Vec<(Point, Label)>,
+}
+
+impl<B: BitBoard> Serialize for EncodingBoard<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut cells = self.points().collect::<Vec<_>>();
+        cells.sort_by(Point::cmp_reading_order);
+        BoardRepr {
+            cells,
+            labels: self
+                .labels()
+                .map(|(point, label)| (point, label.clone()))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, B: BitBoard> Deserialize<'de> for EncodingBoard<B> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BoardRepr::deserialize(deserializer)?;
+        let mut board = EncodingBoard::from_points(repr.cells).map_err(D::Error::custom)?;
+
+        for (point, label) in repr.labels {
+            // Setting a label again would quietly move it, but a repeat here is a mistake
+            if board.point_of(&label).is_some() || board.label_at(point).is_some() {
+                return Err(D::Error::custom(PuzzleError::DuplicateLabel {
+                    point,
+                    label,
+                }));
+            }
+            board.set_label(point, label).map_err(D::Error::custom)?;
+        }
+
+        Ok(board)
+    }
+}
+
+// Only the board, the tiles, and the orientations they're allowed in. The placements are worked
+// out again when reading.
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct PuzzleRef<'a, B: BitBoard> {
+    board: &'a EncodingBoard<B>,
+    tiles: &'a [Tile],
+    transforms: Transforms,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct PuzzleRepr<B: BitBoard> {
+    board: EncodingBoard<B>,
+    tiles: Vec<Tile>,
+    transforms: Transforms,
+}
+
+impl<B: BitBoard> Serialize for Puzzle<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PuzzleRef {
+            board: self.board(),
+            tiles: self.tiles(),
+            transforms: self.transforms(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, B: BitBoard> Deserialize<'de> for Puzzle<B> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PuzzleRepr::<B>::deserialize(deserializer)?;
+        Puzzle::builder()
+            .board(repr.board)
+            .tiles(repr.tiles)
+            .transforms(repr.transforms)
+            .build()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant;
+
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(json: &str) -> Result<T, String> {
+        serde_json::from_str(json).map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn points_and_aabbs_round_trip() {
+        let point = Point { x: -3, y: 7 };
+        assert_eq!(round_trip(&point), point);
+        let aabb = AABB::new(point, Point { x: 2, y: 9 }).unwrap();
+        assert_eq!(round_trip(&aabb), aabb);
+    }
+
+    #[test]
+    fn tiles_round_trip_with_their_transforms() {
+        for mut tile in (variant::classic().tiles)() {
+            assert_eq!(round_trip(&tile), tile);
+            tile.set_allowed_transforms(Transforms::Rotations);
+            let read = round_trip(&tile);
+            assert_eq!(read.allowed_transforms(), Transforms::Rotations);
+        }
+    }
+
+    #[test]
+    fn boards_round_trip_with_their_labels() {
+        let board = (variant::classic().board)();
+        let read = round_trip(&board);
+        assert!(read == board);
+        assert!(read.labels().eq(board.labels()));
+        assert!(read.cells().eq(board.cells()));
+    }
+
+    #[test]
+    fn puzzles_round_trip() {
+        let puzzle = Puzzle::from_variant(variant::classic()).unwrap();
+        let read = round_trip(&puzzle);
+        assert!(*read.board() == *puzzle.board());
+        assert_eq!(read.tiles(), puzzle.tiles());
+        assert_eq!(read.transforms(), puzzle.transforms());
+        assert_eq!(read.configurations(), puzzle.configurations());
+    }
+
+    #[test]
+    fn invalid_aabbs_and_tiles_are_rejected() {
+        let inverted = read::<AABB>(r#"{"min":{"x":1,"y":0},"max":{"x":0,"y":0}}"#);
+        assert!(inverted.is_err());
+
+        let tile = |points: &str| {
+            read::<Tile>(&format!(
+                r#"{{"points":{points},"transforms":"RotationsAndReflections"}}"#
+            ))
+        };
+        assert!(tile(r#"[{"x":0,"y":0},{"x":0,"y":1}]"#).is_ok());
+        let disconnected = tile(r#"[{"x":0,"y":0},{"x":1,"y":1}]"#).unwrap_err();
+        assert!(disconnected.contains("connected"), "{disconnected}");
+        assert!(tile("[]").is_err());
+        assert!(tile(r#"[{"x":0,"y":0},{"x":0,"y":0}]"#).is_err());
+        assert!(read::<Tile>(r#"{"points":[{"x":0,"y":0}],"transforms":"Sideways"}"#).is_err());
+    }
+
+    #[test]
+    fn invalid_boards_and_puzzles_are_rejected() {
+        let cells = |count: i32| {
+            let cells = (0..count)
+                .map(|x| format!(r#"{{"x":{x},"y":0}}"#))
+                .collect::<Vec<_>>();
+            format!("[{}]", cells.join(","))
+        };
+        let board = |cells: &str, labels: &str| {
+            read::<EncodingBoard>(&format!(r#"{{"cells":{cells},"labels":{labels}}}"#))
+        };
+
+        assert!(board(&cells(64), "[]").is_ok());
+        assert!(board(&cells(65), "[]").is_err());
+        let off_board = board(&cells(3), r#"[[{"x":5,"y":0},{"Day":1}]]"#);
+        assert!(off_board.is_err());
+        let repeated = board(
+            &cells(3),
+            r#"[[{"x":0,"y":0},{"Day":1}],[{"x":1,"y":0},{"Day":1}]]"#,
+        );
+        assert!(repeated.is_err());
+
+        // A puzzle's board and tiles are checked like any others
+        let puzzle = Puzzle::from_variant(variant::classic()).unwrap();
+        let mut json = serde_json::to_value(&puzzle).unwrap();
+        json["tiles"][0]["points"] = serde_json::json!([{"x": 0, "y": 0}, {"x": 1, "y": 1}]);
+        assert!(serde_json::from_value::<Puzzle>(json).is_err());
+        let mut json = serde_json::to_value(&puzzle).unwrap();
+        json["board"]["cells"] = serde_json::from_str(&cells(65)).unwrap();
+        assert!(serde_json::from_value::<Puzzle>(json).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backtrack::{self, Limits},
+    check_date, cli,
+    date::Date,
+    entity::DecodingBoard,
+    error::Error,
+    render::{json_string, Format},
+    BitBoard, Context,
+};
+
+const USAGE_HINT: &str = "try GET /solve?month=10&day=13";
+
+// How many connections are answered at once. The rest wait to be accepted.
+const WORKERS: usize = 8;
+// How long a client can take to send its request or read the response before it's dropped, so a
+// slow one can't hold on to a worker
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+// The longest request line, and the most bytes of headers, that are read from a request
+const MAX_REQUEST_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64 * 1024;
+
+// Serves `GET /solve?month=M&day=D` on localhost until the process is stopped. A fixed number of
+// workers take turns accepting connections, and only read from the shared context, so requests
+// don't interfere.
+pub fn run<B: BitBoard>(
+    ctx: &Context<B>,
+    port: u16,
+    timeout: Option<Duration>,
+) -> Result<(), Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|error| {
+        Error::InvalidInput(format!("failed to listen on port {port}: {error}"))
+    })?;
+    eprintln!("Listening on http://127.0.0.1:{port}");
+
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| {
+                for stream in listener.incoming() {
+                    // A connection that failed before it was accepted has nobody to answer to
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    // Failing to respond means the client went away or was too slow, which
+                    // there's nothing to do about
+                    respond(ctx, timeout, stream).ok();
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn respond<B: BitBoard>(
+    ctx: &Context<B>,
+    timeout: Option<Duration>,
+    mut stream: TcpStream,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let (status, body) = match read_request(&mut reader)? {
+        Ok(request_line) => route(ctx, timeout, &request_line),
+        Err(response) => response,
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+// Reads the request line, and then the headers, none of which matter but which have to be read
+// before responding. A request which is too long gets the response to send instead.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<String, (&'static str, String)>> {
+    let Some(request_line) = read_line(reader, MAX_REQUEST_LINE)? else {
+        return Ok(Err((
+            "414 URI Too Long",
+            error_json("too_long", "the request line is too long"),
+        )));
+    };
+
+    let mut left = MAX_HEADERS;
+    loop {
+        let Some(header) = read_line(reader, left)? else {
+            return Ok(Err((
+                "431 Request Header Fields Too Large",
+                error_json("too_long", "the headers are too long"),
+            )));
+        };
+        if header.trim().is_empty() {
+            return Ok(Ok(request_line));
+        }
+        left -= header.len();
+    }
+}
+
+// Reads up to the end of the line, or returns `None` if the line is longer than `limit` bytes.
+// The end of the stream ends the line, so an empty line comes back once nothing's left.
+fn read_line(reader: &mut impl BufRead, limit: usize) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    reader
+        .by_ref()
+        .take(limit as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if line.len() > limit {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+fn route<B: BitBoard>(
+    ctx: &Context<B>,
+    timeout: Option<Duration>,
+    request_line: &str,
+) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return (
+            "400 Bad Request",
+            error_json("bad_request", "malformed request"),
+        );
+    };
+
+    if method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            error_json(
+                "method_not_allowed",
+                &format!("only GET is supported, {USAGE_HINT}"),
+            ),
+        );
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/solve" {
+        return (
+            "404 Not Found",
+            error_json("not_found", &format!("nothing at `{path}`, {USAGE_HINT}")),
+        );
+    }
+
+    match solve(ctx, timeout, query) {
+        Ok(body) => ("200 OK", body),
+        Err(error) => {
+            let status = match error {
+                Error::InvalidInput(..) | Error::Rejected(..) => "400 Bad Request",
+                Error::NoSolution(..) => "404 Not Found",
+                Error::TimedOut(..) => "503 Service Unavailable",
+                Error::Internal(..) => "500 Internal Server Error",
+                // The server writes its responses itself, so nothing here closes them
+                Error::OutputClosed => "500 Internal Server Error",
+            };
+            (status, error.to_json() + "\n")
+        }
+    }
+}
+
+fn solve<B: BitBoard>(
+    ctx: &Context<B>,
+    timeout: Option<Duration>,
+    query: &str,
+) -> Result<String, Error> {
+    let mut month = None;
+    let mut day = None;
+    let mut year = None;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "month" => month = Some(cli::parse_month(value).map_err(Error::InvalidInput)?),
+            "day" => day = Some(cli::parse_day(value).map_err(Error::InvalidInput)?),
+            "year" => year = Some(cli::parse_year(value).map_err(Error::InvalidInput)?),
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "unknown query parameter `{key}`, expected `month`, `day` or `year`"
+                )))
+            }
+        }
+    }
+    let (Some(month), Some(day)) = (month, day) else {
+        return Err(Error::InvalidInput(
+            "both `month` and `day` are required".to_owned(),
+        ));
+    };
+
+    let date = Date { year, month, day };
+    check_date(date, ctx.force)?;
+    let job = ctx.job(date, None)?;
+
+    // The deadline is per request, unlike the one set when the context was configured
+    let started = Instant::now();
+    let solution = backtrack::backtrack(
+        job.initial_board,
+        &job.placements,
+        &job.placement_indices,
+        ctx.order,
+        &Limits::deadline(timeout.map(|timeout| started + timeout)),
+        &mut (),
+    )?;
+    let elapsed = started.elapsed();
+    let solution =
+        solution.ok_or_else(|| Error::NoSolution(format!("No solution found for {date} :(")))?;
+
+    let mut rendering = Vec::new();
+    crate::write_solution(
+        &mut rendering,
+        &mut DecodingBoard::from(&ctx.encoding_board),
+        &ctx.solution(&job, &solution),
+        Format::Json,
+        &ctx.tiles,
+    )?;
+    let rendering = String::from_utf8(rendering).expect("renderings are valid UTF-8");
+
+    Ok(format!(
+        "{{\"date\":{},\"solution\":{},\"elapsed_ms\":{:.3}}}\n",
+        json_string(&date.numeric()),
+        rendering.trim_end(),
+        elapsed.as_secs_f64() * 1000.0
+    ))
+}
+
+fn error_json(kind: &str, message: &str) -> String {
+    format!(
+        "{{\"kind\":{},\"message\":{}}}\n",
+        json_string(kind),
+        json_string(message)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_request() {
+        let request = "GET /solve?month=10&day=13 HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let line = read_request(&mut request.as_bytes()).unwrap();
+        assert_eq!(
+            line,
+            Ok("GET /solve?month=10&day=13 HTTP/1.1\r\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn long_requests_are_refused() {
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_REQUEST_LINE));
+        let response = read_request(&mut long_line.as_bytes()).unwrap();
+        assert_eq!(response.unwrap_err().0, "414 URI Too Long");
+
+        let many_headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(10_000));
+        let response = read_request(&mut many_headers.as_bytes()).unwrap();
+        assert_eq!(
+            response.unwrap_err().0,
+            "431 Request Header Fields Too Large"
+        );
+    }
+
+    #[test]
+    fn lines_end_with_the_stream() {
+        let mut reader = "GET / HTTP/1.1".as_bytes();
+        assert_eq!(
+            read_line(&mut reader, 100).unwrap().as_deref(),
+            Some("GET / HTTP/1.1")
+        );
+        assert_eq!(read_line(&mut reader, 100).unwrap().as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    date::Month,
+    entity::{EncodingBoard, Label, Point, Tile},
+};
+
+macro_rules! aabb {
+    (($x0:literal, $y0:literal) -> ($x1:literal, $y1:literal)) => {
+        $crate::entity::AABB::new(
+            $crate::entity::Point { x: $x0, y: $y0 },
+            $crate::entity::Point { x: $x1, y: $y1 },
+        )
+        .expect("built-in rectangles have their corners in order")
+    };
+}
+
+macro_rules! enc_board {
+    ($( $aabb:expr, )+) => {
+        $crate::entity::EncodingBoard::new(vec![$( $aabb ),+])
+            .expect("built-in boards are small enough and don't overlap")
+    };
+}
+
+// A tile drawn with one string per row, top row first, in the form `Tile::from_ascii` reads.
+// Mistakes in the drawing are caught at compile time.
+macro_rules! tile_art {
+    ($( $row:literal ),+ $(,)?) => {{
+        const _: () = assert!(
+            $crate::variant::is_tile_art(&[$( $row ),+]),
+            "tile drawings may only hold `#`, `X`, `.` and spaces, and need filled cells which \
+             are all connected"
+        );
+        $crate::entity::Tile::from_ascii(concat!($( $row, "\n" ),+))
+            .expect("built-in tiles are checked at compile time")
+    }};
+}
+
+// The largest drawing `is_tile_art` can check, which is far bigger than any tile needs
+const MAX_ART: usize = 16;
+
+// Whether `Tile::from_ascii` would accept the rows, for checking built-in tiles at compile time
+const fn is_tile_art(rows: &[&str]) -> bool {
+    if rows.len() > MAX_ART {
+        return false;
+    }
+
+    let mut grid = [[false; MAX_ART]; MAX_ART];
+    let mut filled = 0;
+    let mut start = (0, 0);
+    let mut row = 0;
+    while row < rows.len() {
+        let bytes = rows[row].as_bytes();
+        if bytes.len() > MAX_ART {
+            return false;
+        }
+        let mut col = 0;
+        while col < bytes.len() {
+            match bytes[col] {
+                b'#' | b'X' => {
+                    grid[row][col] = true;
+                    filled += 1;
+                    start = (row, col);
+                }
+                b'.' | b' ' => (),
+                _ => return false,
+            }
+            col += 1;
+        }
+        row += 1;
+    }
+    filled > 0 && reachable(grid, start) == filled
+}
+
+// How many filled cells of the grid can be reached from `start` through cells sharing an edge
+const fn reachable(mut grid: [[bool; MAX_ART]; MAX_ART], start: (usize, usize)) -> usize {
+    let mut stack = [(0, 0); MAX_ART * MAX_ART];
+    stack[0] = start;
+    grid[start.0][start.1] = false;
+    let mut len = 1;
+    let mut count = 0;
+    while len > 0 {
+        len -= 1;
+        let (row, col) = stack[len];
+        count += 1;
+
+        let neighbors = [
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ];
+        let mut i = 0;
+        while i < neighbors.len() {
+            let (row, col) = neighbors[i];
+            if row < MAX_ART && col < MAX_ART && grid[row][col] {
+                // Cleared as it's pushed, so no cell is counted twice
+                grid[row][col] = false;
+                stack[len] = (row, col);
+                len += 1;
+            }
+            i += 1;
+        }
+    }
+    count
+}
+
+// A built-in edition of the puzzle
+pub struct Variant {
+    pub name: &'static str,
+    // With a label on the cell showing each month and day
+    pub board: fn() -> EncodingBoard,
+    pub tiles: fn() -> Vec<Tile>,
+}
+
+// Every built-in variant. Adding a variant only requires adding an entry here.
+pub const VARIANTS: &[Variant] = &[Variant {
+    name: "classic",
+    board: classic_board,
+    tiles: classic_tiles,
+}];
+
+pub fn find(name: &str) -> Result<&'static Variant, String> {
+    VARIANTS
+        .iter()
+        .find(|variant| variant.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names = VARIANTS
+                .iter()
+                .map(|variant| variant.name)
+                .collect::<Vec<_>>();
+            format!(
+                "unknown variant `{name}`, expected one of: {}",
+                names.join(", ")
+            )
+        })
+}
+
+pub fn classic() -> &'static Variant {
+    &VARIANTS[0]
+}
+
+// Months in two rows of six above the days in rows of seven, each filled from left to right
+fn classic_board() -> EncodingBoard {
+    let mut board = enc_board!(
+        aabb!((0,0) -> (2,0)),
+        aabb!((0,1) -> (6,4)),
+        aabb!((0,5) -> (5,6)),
+    );
+
+    for month in Month::ALL {
+        let month_num = month as i32;
+        let point = Point {
+            x: month_num % 6,
+            y: 6 - month_num / 6,
+        };
+        board
+            .set_label(point, Label::Month(month))
+            .expect("the months are on the board");
+    }
+    for day in 1..=31u8 {
+        let point = Point {
+            x: i32::from(day - 1) % 7,
+            y: 4 - i32::from(day - 1) / 7,
+        };
+        board
+            .set_label(point, Label::Day(day))
+            .expect("the days are on the board");
+    }
+
+    board
+}
+
+#[rustfmt::skip]
+fn classic_tiles() -> Vec<Tile> {
+    vec![
+        tile_art!(
+            "#..",
+            "#..",
+            "###",
+        ),
+        tile_art!(
+            "#.",
+            "#.",
+            "#.",
+            "##",
+        ),
+        tile_art!(
+            ".#",
+            "##",
+            "#.",
+            "#.",
+        ),
+        tile_art!(
+            "##",
+            "##",
+            "##",
+        ),
+        tile_art!(
+            "#.",
+            "##",
+            "##",
+        ),
+        tile_art!(
+            "##",
+            "#.",
+            "##",
+        ),
+        tile_art!(
+            "#.",
+            "#.",
+            "##",
+            "#.",
+        ),
+        tile_art!(
+            "..#",
+            "###",
+            "#..",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Transforms;
+
+    fn tile(points: &[(i32, i32)]) -> Tile {
+        Tile::new(points.iter().map(|&(x, y)| Point { x, y }).collect()).unwrap()
+    }
+
+    // The classic tiles as they were first written, as lists of points
+    #[test]
+    fn classic_tile_drawings_match_their_points() {
+        let points = [
+            tile(&[(0, 2), (0, 1), (0, 0), (1, 0), (2, 0)]),
+            tile(&[(0, 3), (0, 2), (0, 1), (0, 0), (1, 0)]),
+            tile(&[(1, 3), (0, 2), (1, 2), (0, 1), (0, 0)]),
+            tile(&[(0, 2), (1, 2), (0, 1), (1, 1), (0, 0), (1, 0)]),
+            tile(&[(0, 2), (0, 1), (1, 1), (0, 0), (1, 0)]),
+            tile(&[(0, 2), (1, 2), (0, 1), (0, 0), (1, 0)]),
+            tile(&[(0, 3), (0, 2), (0, 1), (1, 1), (0, 0)]),
+            tile(&[(2, 2), (0, 1), (1, 1), (2, 1), (0, 0)]),
+        ];
+        assert_eq!(classic_tiles(), points);
+    }
+
+    #[test]
+    fn classic_tile_orientation_counts() {
+        let counts = |transforms| {
+            classic_tiles()
+                .iter()
+                .map(|tile| tile.orientations(transforms).len())
+                .collect::<Vec<_>>()
+        };
+        // V, L, N, the 2x3 rectangle, P, U, Y and S
+        assert_eq!(
+            counts(Transforms::RotationsAndReflections),
+            [4, 8, 8, 2, 8, 4, 8, 4]
+        );
+        assert_eq!(counts(Transforms::Rotations), [4, 4, 4, 2, 4, 4, 4, 2]);
+        assert_eq!(counts(Transforms::Identity), [1; 8]);
+    }
+
+    #[test]
+    fn tile_art_has_to_be_connected() {
+        assert!(is_tile_art(&["#..", "#..", "###"]));
+        assert!(is_tile_art(&[".#", "##", "#."]));
+        assert!(!is_tile_art(&["#.#"]));
+        assert!(!is_tile_art(&["#.", ".#"]));
+        assert!(!is_tile_art(&["..", ".."]));
+        assert!(!is_tile_art(&["#o"]));
+    }
+
+    // Orientations are distinct shapes, each normalized to the origin
+    #[test]
+    fn orientations_are_distinct() {
+        for tile in classic_tiles() {
+            let orientations = tile.orientations(Transforms::RotationsAndReflections);
+            for (index, orientation) in orientations.iter().enumerate() {
+                assert!(orientation.points().contains(&Point::ORIGIN));
+                assert!(!orientations[..index].contains(orientation));
+            }
+            assert_eq!(orientations[0], tile);
+        }
+    }
+
+    #[test]
+    fn classic_tiles_round_trip_through_their_drawings() {
+        for tile in classic_tiles() {
+            let drawing = crate::render::tile(&tile);
+            assert_eq!(Tile::from_ascii(&drawing), Ok(tile), "{drawing}");
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use crate::{
+    bitboard::BitBoard,
+    entity::{EncodingBoard, Point, Tile, Transforms},
+};
+
+// A piece as described by a user-supplied solution
+pub struct Piece {
+    pub label: String,
+    pub points: Vec<Point>,
+}
+
+// Parses a solution in one of two forms. The first is a grid in the same shape as the board, where
+// covered cells hold the letter or digit of the piece covering them, `#` marks an uncovered cell,
+// and spaces lie off the board. The second lists one piece per line as `LABEL: (x,y) (x,y) ...`
+// in board coordinates.
+pub fn parse_solution(src: &str) -> Result<Vec<Piece>, String> {
+    if src.contains(':') {
+        parse_coordinates(src)
+    } else {
+        parse_grid(src).map(|(pieces, _)| pieces)
+    }
+}
+
+// Parses a partly filled board, drawn as a grid where `.` marks the cells still to be covered.
+// Returns the pieces along with the cells marked `#`, which show the date.
+pub fn parse_partial(src: &str) -> Result<(Vec<Piece>, Vec<Point>), String> {
+    if src.contains(':') {
+        return Err(
+            "expected a grid of piece letters, with `.` for empty cells and `#` for the date"
+                .to_owned(),
+        );
+    }
+    parse_grid(src)
+}
+
+fn parse_grid(src: &str) -> Result<(Vec<Piece>, Vec<Point>), String> {
+    let mut lines = src.lines().collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let mut pieces = Vec::<Piece>::new();
+    let mut marked = Vec::new();
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let y = (lines.len() - 1 - line_idx) as i32;
+
+        for (x, ch) in line.chars().enumerate() {
+            let point = Point { x: x as i32, y };
+
+            match ch {
+                ' ' | '.' => (),
+                '#' => marked.push(point),
+                ch if ch.is_ascii_alphanumeric() => {
+                    let label = ch.to_string();
+                    match pieces.iter_mut().find(|piece| piece.label == label) {
+                        Some(piece) => piece.points.push(point),
+                        None => pieces.push(Piece {
+                            label,
+                            points: vec![point],
+                        }),
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "unexpected character `{ch}` on line {} column {}, expected a letter, a digit, `#`, `.`, or a space",
+                        line_idx + 1,
+                        x + 1
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok((pieces, marked))
+}
+
+fn parse_coordinates(src: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+
+    for (line_idx, line) in src.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let invalid = || {
+            format!(
+                "line {} should look like `LABEL: (x,y) (x,y) ...`",
+                line_idx + 1
+            )
+        };
+
+        let (label, rest) = line.split_once(':').ok_or_else(invalid)?;
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(invalid());
+        }
+        // As printed by `--format coords`; the uncovered cells are checked against the date anyway
+        if label == "uncovered" || label == "extra" {
+            continue;
+        }
+
+        let mut points = Vec::new();
+        let mut rest = rest.trim();
+        while !rest.is_empty() {
+            let (pair, tail) = rest
+                .strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .ok_or_else(invalid)?;
+            let (x, y) = pair.split_once(',').ok_or_else(invalid)?;
+            let x = x.trim().parse::<i32>().map_err(|_| invalid())?;
+            let y = y.trim().parse::<i32>().map_err(|_| invalid())?;
+
+            points.push(Point { x, y });
+            rest = tail.trim_start();
+        }
+
+        pieces.push(Piece {
+            label: label.to_owned(),
+            points,
+        });
+    }
+
+    Ok(pieces)
+}
+
+// Translates the points so their minimum x and y are zero and sorts them, so that two shapes are
+// the same up to translation exactly when their normalized forms are equal
+fn normalized(points: impl Iterator<Item = Point>) -> Vec<Point> {
+    let mut points = points.collect::<Vec<_>>();
+    let min = Point {
+        x: points.iter().map(|point| point.x).min().unwrap_or(0),
+        y: points.iter().map(|point| point.y).min().unwrap_or(0),
+    };
+
+    for point in &mut points {
+        *point -= min;
+    }
+    points.sort_by_key(|point| (point.x, point.y));
+
+    points
+}
+
+// Every distinct orientation of the tile allowed by `transforms`, in normalized form
+fn orientations(tile: &Tile, transforms: Transforms) -> Vec<Vec<Point>> {
+    tile.orientations(transforms)
+        .iter()
+        .map(|tile| normalized(tile.points().iter().copied()))
+        .collect()
+}
+
+// Checks that the pieces form a valid solution for the date whose cells are `date_points`,
+// returning a description of every problem found otherwise
+pub fn verify<B: BitBoard>(
+    encoding_board: &EncodingBoard<B>,
+    tiles: &[Tile],
+    date_points: &[Point],
+    pieces: &[Piece],
+    transforms: Transforms,
+) -> Result<(), Vec<String>> {
+    let (assigned, mut problems) =
+        check_pieces(encoding_board, tiles, date_points, pieces, transforms);
+
+    for index in (0..tiles.len()).filter(|index| !assigned.contains(&Some(*index))) {
+        problems.push(format!("tile {index} was not used"));
+    }
+
+    // With fewer tiles than the board needs, exactly as many cells as are missing may stay uncovered
+    let tile_area = tiles.iter().map(|tile| tile.area()).sum::<usize>();
+    let spare = encoding_board
+        .points()
+        .count()
+        .saturating_sub(date_points.len() + tile_area);
+    let extra = encoding_board
+        .points()
+        .filter(|&point| {
+            !date_points.contains(&point)
+                && !pieces.iter().any(|piece| piece.points.contains(&point))
+        })
+        .collect::<Vec<_>>();
+    if extra.len() != spare {
+        for point in extra {
+            problems.push(format!(
+                "cell ({}, {}) was left uncovered, but it isn't the month or the day",
+                point.x, point.y
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+// Checks that the pieces could be part of a solution for the date, without requiring every tile
+// to be placed. Returns the index of the tile each piece was matched to.
+pub fn verify_partial<B: BitBoard>(
+    encoding_board: &EncodingBoard<B>,
+    tiles: &[Tile],
+    date_points: &[Point],
+    pieces: &[Piece],
+    transforms: Transforms,
+) -> Result<Vec<usize>, Vec<String>> {
+    let (assigned, problems) = check_pieces(encoding_board, tiles, date_points, pieces, transforms);
+
+    if problems.is_empty() {
+        Ok(assigned.into_iter().flatten().collect())
+    } else {
+        Err(problems)
+    }
+}
+
+// The checks shared by complete and partial solutions, returning the tile matched to each piece
+// along with the problems found
+fn check_pieces<B: BitBoard>(
+    encoding_board: &EncodingBoard<B>,
+    tiles: &[Tile],
+    date_points: &[Point],
+    pieces: &[Piece],
+    transforms: Transforms,
+) -> (Vec<Option<usize>>, Vec<String>) {
+    let mut problems = Vec::new();
+    let mut covered_by = HashMap::<Point, &str>::new();
+
+    for piece in pieces {
+        for &point in &piece.points {
+            if encoding_board.encode([point].into_iter()).is_none() {
+                problems.push(format!(
+                    "piece {} covers ({}, {}), which is not on the board",
+                    piece.label, point.x, point.y
+                ));
+                continue;
+            }
+
+            if let Some(other) = covered_by.insert(point, &piece.label) {
+                problems.push(format!(
+                    "cell ({}, {}) is covered by both piece {other} and piece {}",
+                    point.x, point.y, piece.label
+                ));
+            }
+        }
+    }
+
+    // Tiles of the same shape are interchangeable, so matching each piece to the first unused tile
+    // of its shape is enough to find a valid assignment whenever one exists
+    let tile_orientations = tiles
+        .iter()
+        .map(|tile| orientations(tile, transforms))
+        .collect::<Vec<_>>();
+    let mut assigned = Vec::with_capacity(pieces.len());
+
+    for piece in pieces {
+        let shape = normalized(piece.points.iter().copied());
+        let matching = (0..tiles.len()).find(|&index| {
+            !assigned.contains(&Some(index)) && tile_orientations[index].contains(&shape)
+        });
+        assigned.push(matching);
+
+        let same_shape = (0..tiles.len())
+            .filter(|&index| tile_orientations[index].contains(&shape))
+            .collect::<Vec<_>>();
+        match matching {
+            Some(_) => (),
+            None if !same_shape.is_empty() => {
+                let claimed_by = pieces
+                    .iter()
+                    .zip(&assigned)
+                    .filter(|(_, tile)| tile.is_some_and(|tile| same_shape.contains(&tile)))
+                    .map(|(other, _)| other.label.as_str())
+                    .collect::<Vec<_>>();
+                let same_shape = same_shape.iter().map(usize::to_string).collect::<Vec<_>>();
+                problems.push(if same_shape.len() == 1 {
+                    format!(
+                        "piece {} has the shape of tile {}, but piece {} already claimed it",
+                        piece.label, same_shape[0], claimed_by[0]
+                    )
+                } else {
+                    format!(
+                        "piece {} has the shape of tiles {}, but pieces {} already claimed them",
+                        piece.label,
+                        same_shape.join(" and "),
+                        claimed_by.join(" and ")
+                    )
+                })
+            }
+            None => problems.push(format!(
+                "piece {} does not match the shape of any tile under any rotation or reflection",
+                piece.label
+            )),
+        }
+    }
+
+    for point in date_points {
+        if let Some(label) = covered_by.get(point) {
+            problems.push(format!(
+                "cell ({}, {}) must be left uncovered for the date, but piece {label} covers it",
+                point.x, point.y
+            ));
+        }
+    }
+
+    (assigned, problems)
+}